@@ -0,0 +1,268 @@
+use super::node::{JsonArrayIter, JsonNode, JsonObjectIter};
+use super::parser::{JsonValue, Number};
+use super::string::unescape;
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
+use core::fmt;
+use serde::de::{
+    DeserializeSeed, Deserializer, EnumAccess, IntoDeserializer, MapAccess, SeqAccess,
+    VariantAccess, Visitor,
+};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct DeError(String);
+
+impl fmt::Display for DeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl serde::de::StdError for DeError {}
+
+impl serde::de::Error for DeError {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        DeError(msg.to_string())
+    }
+}
+
+fn unescape_or_error(s: &str) -> Result<Cow<'_, str>, DeError> {
+    unescape(s).ok_or_else(|| DeError("invalid escape".to_string()))
+}
+
+struct SeqDeserializer<'de> {
+    iter: JsonArrayIter<'de>,
+}
+
+impl<'de> SeqAccess<'de> for SeqDeserializer<'de> {
+    type Error = DeError;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, DeError> {
+        match self.iter.next() {
+            Some(node) => seed.deserialize(node).map(Some),
+            None => Ok(None),
+        }
+    }
+}
+
+struct MapDeserializer<'de> {
+    iter: JsonObjectIter<'de>,
+    value: Option<JsonNode<'de>>,
+}
+
+impl<'de> MapAccess<'de> for MapDeserializer<'de> {
+    type Error = DeError;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, DeError> {
+        match self.iter.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                match unescape_or_error(key)? {
+                    Cow::Borrowed(key) => seed.deserialize(key.into_deserializer()).map(Some),
+                    Cow::Owned(key) => seed.deserialize(key.into_deserializer()).map(Some),
+                }
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, DeError> {
+        match self.value.take() {
+            Some(value) => seed.deserialize(value),
+            None => Err(DeError("value without key".to_string())),
+        }
+    }
+}
+
+// "Variant" or {"Variant": content}
+struct EnumDeserializer<'de> {
+    variant: Cow<'de, str>,
+    content: Option<JsonNode<'de>>,
+}
+
+impl<'de> EnumAccess<'de> for EnumDeserializer<'de> {
+    type Error = DeError;
+    type Variant = Self;
+
+    fn variant_seed<V: DeserializeSeed<'de>>(self, seed: V) -> Result<(V::Value, Self), DeError> {
+        let variant = match &self.variant {
+            Cow::Borrowed(variant) => seed.deserialize(variant.into_deserializer())?,
+            Cow::Owned(variant) => seed.deserialize(variant.as_str().into_deserializer())?,
+        };
+        Ok((variant, self))
+    }
+}
+
+impl<'de> VariantAccess<'de> for EnumDeserializer<'de> {
+    type Error = DeError;
+
+    fn unit_variant(self) -> Result<(), DeError> {
+        match self.content {
+            None => Ok(()),
+            Some(_) => Err(DeError("expected unit variant".to_string())),
+        }
+    }
+
+    fn newtype_variant_seed<T: DeserializeSeed<'de>>(self, seed: T) -> Result<T::Value, DeError> {
+        match self.content {
+            Some(content) => seed.deserialize(content),
+            None => Err(DeError("expected newtype variant".to_string())),
+        }
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value, DeError> {
+        match self.content {
+            Some(content) => content.deserialize_seq(visitor),
+            None => Err(DeError("expected tuple variant".to_string())),
+        }
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        match self.content {
+            Some(content) => content.deserialize_map(visitor),
+            None => Err(DeError("expected struct variant".to_string())),
+        }
+    }
+}
+
+impl<'de> Deserializer<'de> for JsonNode<'de> {
+    type Error = DeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match self.value() {
+            JsonValue::Null() => visitor.visit_unit(),
+            JsonValue::True() => visitor.visit_bool(true),
+            JsonValue::False() => visitor.visit_bool(false),
+            JsonValue::Number(..) => match self.as_number() {
+                Some(Number::Int(n)) => visitor.visit_i64(n),
+                Some(Number::UInt(n)) => visitor.visit_u64(n),
+                Some(Number::Float(n)) => visitor.visit_f64(n),
+                None => Err(DeError("invalid number".to_string())),
+            },
+            JsonValue::String(_) => match self.get_str() {
+                Some(Cow::Borrowed(s)) => visitor.visit_borrowed_str(s),
+                Some(Cow::Owned(s)) => visitor.visit_string(s),
+                None => Err(DeError("invalid escape".to_string())),
+            },
+            JsonValue::ArrayOpen(_) => visitor.visit_seq(SeqDeserializer {
+                iter: self.array_iter(),
+            }),
+            JsonValue::ObjectOpen(_) => visitor.visit_map(MapDeserializer {
+                iter: self.object_iter(),
+                value: None,
+            }),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, DeError> {
+        match self.value() {
+            JsonValue::Null() => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, DeError> {
+        match self.value() {
+            JsonValue::String(_) => visitor.visit_enum(EnumDeserializer {
+                variant: self
+                    .get_str()
+                    .ok_or_else(|| DeError("invalid escape".to_string()))?,
+                content: None,
+            }),
+            JsonValue::ObjectOpen(_) if self.object_len() == Some(1) => {
+                let (variant, content) = self.object_iter().next().unwrap();
+                visitor.visit_enum(EnumDeserializer {
+                    variant: unescape_or_error(variant)?,
+                    content: Some(content),
+                })
+            }
+            _ => Err(DeError(
+                "expected string or single member object".to_string(),
+            )),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+#[test]
+fn deserialize_tests() {
+    use super::parser::JsonParser;
+    use serde::Deserialize;
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    enum Mode {
+        Points,
+        Lines(u32),
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Asset<'a> {
+        version: &'a str,
+        generator: Option<String>,
+    }
+
+    #[derive(Deserialize, Debug, PartialEq)]
+    struct Gltf<'a> {
+        #[serde(borrow)]
+        asset: Asset<'a>,
+        scene: Option<usize>,
+        scale: [f32; 3],
+        names: Vec<String>,
+        modes: Vec<Mode>,
+    }
+
+    let parser = JsonParser::process(
+        r##"{
+    "asset": { "version": "2.0", "generator": null },
+    "scale": [1, 2.5, -1],
+    "names": ["a", "b\"c"],
+    "modes": ["Points", {"Lines": 3}],
+    "extras": { "ignored": true }
+}"##,
+    );
+    let gltf = Gltf::deserialize(JsonNode::new(&parser)).unwrap();
+    assert_eq!(
+        Gltf {
+            asset: Asset {
+                version: "2.0",
+                generator: None,
+            },
+            scene: None,
+            scale: [1.0, 2.5, -1.0],
+            names: vec!["a".to_string(), "b\"c".to_string()],
+            modes: vec![Mode::Points, Mode::Lines(3)],
+        },
+        gltf
+    );
+
+    let parser = JsonParser::process(r##"{"asset": {"version": 2}}"##);
+    assert!(Gltf::deserialize(JsonNode::new(&parser)).is_err());
+}
@@ -0,0 +1,302 @@
+use super::parser::*;
+use alloc::vec::Vec;
+
+// counts are known at the start when replaying tokens, and at the end when streaming
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Event<'a> {
+    StartObject(Option<usize>), // member count
+    EndObject(usize),           // member count
+    StartArray(Option<usize>),  // element count
+    EndArray(usize),            // element count
+    Key(&'a str),               // without double quote
+    Value(JsonValue, &'a str),  // scalar and its source slice
+}
+
+struct EventParser<'a, 'f, F: FnMut(Event<'a>)> {
+    src: &'a str,
+    callback: &'f mut F,
+}
+
+fn skip_whitespace(it: &mut PeekIt) -> Result<(usize, char), ParseError> {
+    it.skip_whitespace();
+    it.peek().ok_or(ParseError::Eof())
+}
+
+fn scalar_len(value: JsonValue) -> usize {
+    match value {
+        JsonValue::Null() => 4,
+        JsonValue::True() => 4,
+        JsonValue::False() => 5,
+        JsonValue::Number(n, _) => n,
+        JsonValue::String(n) => n,
+        _ => panic!(),
+    }
+}
+
+impl<'a, 'f, F: FnMut(Event<'a>)> EventParser<'a, 'f, F> {
+    fn scalar(&mut self, token: JsonToken) {
+        if let JsonTokenData::Value(value) = token.data() {
+            let slice = &self.src[token.start()..token.start() + scalar_len(value)];
+            (self.callback)(Event::Value(value, slice));
+        }
+    }
+
+    fn key(&mut self, it: &mut PeekIt) -> Result<(), ParseError> {
+        let (i, c) = skip_whitespace(it)?;
+        if c != '"' {
+            return Err(ParseError::Unknown(i, c));
+        }
+        it.next();
+        let token = JsonToken::get_string_token(it, i)?;
+        if let JsonTokenData::Value(JsonValue::String(len)) = token.data() {
+            (self.callback)(Event::Key(&self.src[i + 1..i + len - 1]));
+        }
+
+        // :
+        let (i, c) = skip_whitespace(it)?;
+        if c != ':' {
+            return Err(ParseError::Unknown(i, c));
+        }
+        it.next();
+        Ok(())
+    }
+
+    fn array(&mut self, it: &mut PeekIt) -> Result<(), ParseError> {
+        (self.callback)(Event::StartArray(None));
+
+        // close or value
+        let (_, c) = skip_whitespace(it)?;
+        if c == ']' {
+            it.next();
+            (self.callback)(Event::EndArray(0));
+            return Ok(());
+        }
+
+        let mut count = 0;
+        loop {
+            self.value(it)?;
+            count += 1;
+
+            // comma or close
+            let (i, c) = skip_whitespace(it)?;
+            it.next();
+            match c {
+                ',' => (), // continue
+                ']' => {
+                    (self.callback)(Event::EndArray(count));
+                    return Ok(());
+                }
+                _ => return Err(ParseError::Unknown(i, c)),
+            }
+        }
+    }
+
+    fn object(&mut self, it: &mut PeekIt) -> Result<(), ParseError> {
+        (self.callback)(Event::StartObject(None));
+
+        // close or key
+        let (_, c) = skip_whitespace(it)?;
+        if c == '}' {
+            it.next();
+            (self.callback)(Event::EndObject(0));
+            return Ok(());
+        }
+
+        let mut count = 0;
+        loop {
+            self.key(it)?;
+            self.value(it)?;
+            count += 1;
+
+            // comma or close
+            let (i, c) = skip_whitespace(it)?;
+            it.next();
+            match c {
+                ',' => (), // continue
+                '}' => {
+                    (self.callback)(Event::EndObject(count));
+                    return Ok(());
+                }
+                _ => return Err(ParseError::Unknown(i, c)),
+            }
+        }
+    }
+
+    fn value(&mut self, it: &mut PeekIt) -> Result<(), ParseError> {
+        let (i, c) = skip_whitespace(it)?;
+        it.next();
+        match c {
+            'n' => self.scalar(JsonToken::get_null_token(it, i)?),
+            't' => self.scalar(JsonToken::get_true_token(it, i)?),
+            'f' => self.scalar(JsonToken::get_false_token(it, i)?),
+            '0'..='9' | '-' => self.scalar(JsonToken::get_number_token(it, i)?),
+            '"' => self.scalar(JsonToken::get_string_token(it, i)?),
+            '[' => self.array(it)?,
+            '{' => self.object(it)?,
+            _ => return Err(ParseError::Unknown(i, c)),
+        }
+        Ok(())
+    }
+}
+
+// parse without retaining tokens. callback is called in document order.
+pub fn parse_events<'a, F: FnMut(Event<'a>)>(
+    src: &'a str,
+    callback: &mut F,
+) -> Result<(), ParseError> {
+    let mut parser = EventParser { src, callback };
+    let mut it = PeekIt::new(src.char_indices());
+    it.next();
+    parser.value(&mut it)
+}
+
+pub struct JsonEventIter<'a> {
+    parser: &'a JsonParser<'a>,
+    current: usize,
+    // None: array, Some(true): object expecting key, Some(false): object expecting value
+    stack: Vec<Option<bool>>,
+}
+
+impl<'a> JsonEventIter<'a> {
+    fn value_started(&mut self) {
+        if let Some(Some(expect_key)) = self.stack.last_mut() {
+            *expect_key = true;
+        }
+    }
+}
+
+impl<'a> Iterator for JsonEventIter<'a> {
+    type Item = Event<'a>;
+
+    fn next(&mut self) -> Option<Event<'a>> {
+        let index = self.current;
+        let token = self.parser.tokens.get(index)?;
+        self.current += 1;
+
+        match token.data() {
+            JsonTokenData::ArrayClose(count) => {
+                self.stack.pop();
+                Some(Event::EndArray(count))
+            }
+            JsonTokenData::ObjectClose(count) => {
+                self.stack.pop();
+                Some(Event::EndObject(count))
+            }
+            JsonTokenData::Value(JsonValue::ArrayOpen(close_index)) => {
+                self.value_started();
+                self.stack.push(None);
+                match self.parser.tokens[close_index].data() {
+                    JsonTokenData::ArrayClose(count) => Some(Event::StartArray(Some(count))),
+                    _ => panic!(),
+                }
+            }
+            JsonTokenData::Value(JsonValue::ObjectOpen(close_index)) => {
+                self.value_started();
+                self.stack.push(Some(true));
+                match self.parser.tokens[close_index].data() {
+                    JsonTokenData::ObjectClose(count) => Some(Event::StartObject(Some(count))),
+                    _ => panic!(),
+                }
+            }
+            JsonTokenData::Value(value) => {
+                if let Some(Some(expect_key)) = self.stack.last_mut() {
+                    if *expect_key {
+                        *expect_key = false;
+                        return self.parser.get_string(index).map(Event::Key);
+                    }
+                }
+                self.value_started();
+                Some(Event::Value(value, self.parser.get_slice(index)))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl<'a> JsonParser<'a> {
+    // replay the stored tokens as events
+    pub fn events(&'a self) -> JsonEventIter<'a> {
+        JsonEventIter {
+            parser: self,
+            current: 0,
+            stack: Vec::new(),
+        }
+    }
+}
+
+#[test]
+fn parse_events_tests() {
+    let mut events = Vec::new();
+    parse_events(
+        r##"{"a": [1, true], "b": {"c": null}, "d": "x"}"##,
+        &mut |e| {
+            events.push(match e {
+                Event::StartObject(None) => "{".to_string(),
+                Event::EndObject(n) => format!("}}{}", n),
+                Event::StartArray(None) => "[".to_string(),
+                Event::EndArray(n) => format!("]{}", n),
+                Event::Key(key) => format!("key:{}", key),
+                Event::Value(_, slice) => format!("value:{}", slice),
+                _ => panic!("count at the start while streaming"),
+            })
+        },
+    )
+    .unwrap();
+
+    assert_eq!(
+        vec![
+            "{",
+            "key:a",
+            "[",
+            "value:1",
+            "value:true",
+            "]2",
+            "key:b",
+            "{",
+            "key:c",
+            "value:null",
+            "}1",
+            "key:d",
+            r##"value:"x""##,
+            "}3"
+        ],
+        events
+    );
+
+    assert!(parse_events("[1, 2", &mut |_| {}).is_err());
+    assert!(parse_events(r##"{"a" 1}"##, &mut |_| {}).is_err());
+}
+
+#[test]
+fn node_events_tests() {
+    let parser = JsonParser::process(r##"{"a": [1, 2, 3], "b": {}, "c": "x"}"##);
+    let events: Vec<Event> = parser.events().collect();
+    assert_eq!(13, events.len());
+    assert_eq!(Event::StartObject(Some(3)), events[0]);
+    assert_eq!(Event::Key("a"), events[1]);
+    assert_eq!(Event::StartArray(Some(3)), events[2]);
+    assert!(matches!(events[3], Event::Value(_, "1")));
+    assert!(matches!(events[5], Event::Value(_, "3")));
+    assert_eq!(Event::EndArray(3), events[6]);
+    assert_eq!(Event::Key("b"), events[7]);
+    assert_eq!(Event::StartObject(Some(0)), events[8]);
+    assert_eq!(Event::EndObject(0), events[9]);
+    assert_eq!(Event::Key("c"), events[10]);
+    assert!(matches!(events[11], Event::Value(_, r##""x""##)));
+    assert_eq!(Event::EndObject(3), events[12]);
+
+    // same events as streaming, except the counts at the start
+    let src = r##"{"a": [1, {"b": null}], "c": []}"##;
+    let mut streamed = Vec::new();
+    parse_events(src, &mut |e| streamed.push(e)).unwrap();
+    let parser = JsonParser::process(src);
+    let replayed: Vec<Event> = parser
+        .events()
+        .map(|e| match e {
+            Event::StartObject(_) => Event::StartObject(None),
+            Event::StartArray(_) => Event::StartArray(None),
+            e => e,
+        })
+        .collect();
+    assert_eq!(streamed, replayed);
+}
@@ -0,0 +1,124 @@
+use super::node::JsonNode;
+use super::string::unescape;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+
+// supported subset
+//   path     = "$" segment*
+//   segment  = "." name | ".*" | "[" selector "]"
+//   selector = index | "'" key "'" | '"' key '"' | "*"
+// name is up to the next "." or "[". quoted keys have no escapes.
+// "*" selects every element of an array or every member value of an object
+enum Segment {
+    Key(String),
+    Index(usize),
+    Wildcard,
+}
+
+fn parse_path(expr: &str) -> Option<Vec<Segment>> {
+    let mut rest = expr.trim().strip_prefix('$')?;
+    let mut segments = Vec::new();
+    while !rest.is_empty() {
+        if let Some(r) = rest.strip_prefix('.') {
+            let end = r.find(['.', '[']).unwrap_or(r.len());
+            let segment = match &r[..end] {
+                "" => return None,
+                "*" => Segment::Wildcard,
+                name => Segment::Key(String::from(name)),
+            };
+            segments.push(segment);
+            rest = &r[end..];
+        } else if let Some(r) = rest.strip_prefix('[') {
+            let (segment, r) = match r.chars().next()? {
+                quote @ ('\'' | '"') => {
+                    let end = r[1..].find(quote)? + 1;
+                    (Segment::Key(String::from(&r[1..end])), &r[end + 1..])
+                }
+                _ => {
+                    let end = r.find(']')?;
+                    let segment = match r[..end].trim() {
+                        "*" => Segment::Wildcard,
+                        index => Segment::Index(index.parse().ok()?),
+                    };
+                    (segment, &r[end..])
+                }
+            };
+            segments.push(segment);
+            rest = r.strip_prefix(']')?;
+        } else {
+            return None;
+        }
+    }
+    Some(segments)
+}
+
+impl<'a> JsonNode<'a> {
+    // matching nodes in document order. empty if expr is not in the subset above
+    pub fn jsonpath(&self, expr: &str) -> Vec<JsonNode<'a>> {
+        let segments = match parse_path(expr) {
+            Some(segments) => segments,
+            None => return Vec::new(),
+        };
+
+        let mut current = vec![*self];
+        for segment in &segments {
+            let mut next = Vec::new();
+            for node in &current {
+                match segment {
+                    Segment::Key(name) => {
+                        next.extend(
+                            node.object_iter()
+                                .filter(|(key, _)| unescape(key).is_some_and(|key| key == *name))
+                                .map(|(_, value)| value),
+                        );
+                    }
+                    Segment::Index(index) => next.extend(node.try_get(*index)),
+                    Segment::Wildcard => {
+                        next.extend(node.array_iter());
+                        next.extend(node.object_iter().map(|(_, value)| value));
+                    }
+                }
+            }
+            current = next;
+        }
+        current
+    }
+}
+
+#[test]
+fn jsonpath_tests() {
+    use super::parser::JsonParser;
+
+    let parser = JsonParser::process(
+        r##"{
+    "asset": { "version": "2.0" },
+    "meshes": [ { "name": "cube" }, { "primitives": [] }, { "name": "sphere" } ],
+    "a.b": { "c": 1 }
+}"##,
+    );
+    let node = JsonNode::new(&parser);
+    let strings = |expr: &str| -> Vec<String> {
+        node.jsonpath(expr)
+            .iter()
+            .map(|n| String::from(n.slice()))
+            .collect()
+    };
+
+    assert_eq!(vec![r##""2.0""##], strings("$.asset.version"));
+    assert_eq!(vec![r##""2.0""##], strings("$['asset'][\"version\"]"));
+    assert_eq!(
+        vec![r##""cube""##, r##""sphere""##],
+        strings("$.meshes[*].name")
+    );
+    assert_eq!(vec!["[]"], strings("$.meshes[1].primitives"));
+    assert_eq!(vec!["1"], strings("$['a.b'].c"));
+    assert_eq!(3, node.jsonpath("$.*").len());
+    assert_eq!(1, node.jsonpath("$").len());
+
+    assert!(node.jsonpath("$.meshes[3]").is_empty());
+    assert!(node.jsonpath("$.none").is_empty());
+    assert!(node.jsonpath("asset").is_empty());
+    assert!(node.jsonpath("$.meshes[x]").is_empty());
+    assert!(node.jsonpath("$.meshes[0").is_empty());
+}
@@ -0,0 +1,53 @@
+use super::parser::*;
+use alloc::borrow::Cow;
+use alloc::vec::Vec;
+
+// keeps the mapping alive while the tokens refer to it
+pub struct JsonParserMmap {
+    mmap: memmap2::Mmap,
+    tokens: Vec<JsonToken>,
+}
+
+impl JsonParserMmap {
+    pub fn parser(&self) -> JsonParser<'_> {
+        // utf-8 is validated in from_mmap and the mapping is read only
+        let src = unsafe { core::str::from_utf8_unchecked(&self.mmap) };
+        JsonParser {
+            src: Cow::Borrowed(src),
+            tokens: Cow::Borrowed(&self.tokens),
+        }
+    }
+}
+
+impl<'a> JsonParser<'a> {
+    pub fn from_mmap<P: AsRef<std::path::Path>>(path: P) -> Result<JsonParserMmap, ParseError> {
+        let file = std::fs::File::open(path).map_err(|error| ParseError::Io(error.kind()))?;
+        // the file must not be modified while mapped
+        let mmap =
+            unsafe { memmap2::Mmap::map(&file) }.map_err(|error| ParseError::Io(error.kind()))?;
+        let src = core::str::from_utf8(&mmap)
+            .map_err(|_| ParseError::Io(std::io::ErrorKind::InvalidData))?;
+        let tokens = JsonParser::try_process(src)?.tokens.into_owned();
+        Ok(JsonParserMmap { mmap, tokens })
+    }
+}
+
+#[test]
+fn from_mmap_tests() {
+    let src = r##"{"asset": {"version": "2.0"}, "nodes": [1, 2, 3]}"##;
+    let path = std::env::temp_dir().join(format!("jsonprops_mmap_{}.json", std::process::id()));
+    std::fs::write(&path, src).unwrap();
+
+    let mapped = JsonParser::from_mmap(&path).unwrap();
+    let expected = JsonParser::process(src);
+    assert_eq!(expected.tokens, mapped.parser().tokens);
+    assert_eq!(src, mapped.parser().src);
+
+    std::fs::write(&path, [b'"', 0xff, b'"']).unwrap();
+    assert!(matches!(
+        JsonParser::from_mmap(&path),
+        Err(ParseError::Io(std::io::ErrorKind::InvalidData))
+    ));
+
+    std::fs::remove_file(&path).unwrap();
+}
@@ -1,3 +1,18 @@
-pub mod parser;
+#[cfg(feature = "serde")]
+pub mod de;
+pub mod events;
+pub mod jsonpath;
+#[cfg(feature = "mmap")]
+pub mod mmap;
 pub mod node;
+pub mod parser;
+#[cfg(feature = "schema")]
 pub mod schema;
+#[cfg(feature = "serde")]
+pub mod ser;
+pub mod string;
+pub mod testing;
+pub mod value;
+
+pub use parser::is_valid;
+pub use string::{escape_string, escape_string_slash};
@@ -1,4 +1,29 @@
 use super::parser::*;
+use super::string::{pointer_token, unescape, write_escaped, UnescapeCache};
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::format;
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct DocumentStats {
+    pub objects: usize,
+    pub arrays: usize,
+    pub strings: usize, // keys are not counted
+    pub numbers: usize,
+    pub booleans: usize,
+    pub nulls: usize,
+    pub max_depth: usize, // container nesting. 0 for a scalar
+}
+
+fn write_newline(dst: &mut String, indent: usize) {
+    dst.push('\n');
+    for _ in 0..indent {
+        dst.push_str("  ");
+    }
+}
 
 #[derive(Clone, Copy)]
 pub struct JsonNode<'a> {
@@ -6,16 +31,73 @@ pub struct JsonNode<'a> {
     index: usize,
 }
 
-impl<'a> std::fmt::Display for JsonNode<'a> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+impl<'a> core::fmt::Display for JsonNode<'a> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(f, "{}", self.parser.get_slice(self.index))
     }
 }
 
-#[derive(Debug, Clone)]
-pub struct JsonNodeError {}
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
+    NotAnArray,
+    NotAnObject,
+    NotAString,
+    NotABoolean,
+    NotNull,
+    IndexOutOfRange { index: usize, len: usize },
+    KeyNotFound(String),
+    // with DuplicateKeyPolicy::Error
+    DuplicateKey(String),
+    // valid json the caller does not handle. e.g. allOf with two schemas
+    Unsupported,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct JsonNodeError {
+    pub kind: ErrorKind,
+    // token index of the node the access started from
+    pub index: usize,
+}
+
+impl JsonNodeError {
+    pub fn new(node: &JsonNode, kind: ErrorKind) -> JsonNodeError {
+        JsonNodeError {
+            kind,
+            index: node.index,
+        }
+    }
+}
+
+impl core::fmt::Display for JsonNodeError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "node {}: ", self.index)?;
+        match &self.kind {
+            ErrorKind::NotAnArray => write!(f, "not an array"),
+            ErrorKind::NotAnObject => write!(f, "not an object"),
+            ErrorKind::NotAString => write!(f, "not a string"),
+            ErrorKind::NotABoolean => write!(f, "not a boolean"),
+            ErrorKind::NotNull => write!(f, "not null"),
+            ErrorKind::IndexOutOfRange { index, len } => {
+                write!(f, "index {} is out of range for length {}", index, len)
+            }
+            ErrorKind::KeyNotFound(key) => write!(f, "key {} is not found", key),
+            ErrorKind::DuplicateKey(key) => write!(f, "key {} is duplicated", key),
+            ErrorKind::Unsupported => write!(f, "unsupported"),
+        }
+    }
+}
 pub type JsonNodeResult<'a> = Result<JsonNode<'a>, JsonNodeError>;
 
+// which member key lookups return when an object has the same key twice
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuplicateKeyPolicy {
+    First,
+    // same as JSON.parse
+    Last,
+    // duplicated key is an error
+    Error,
+}
+
 pub struct JsonArrayIter<'a> {
     parser: &'a JsonParser<'a>,
     current: usize,
@@ -28,6 +110,40 @@ pub struct JsonObjectIter<'a> {
     end: usize,
 }
 
+impl<'a> Iterator for JsonArrayIter<'a> {
+    type Item = JsonNode<'a>;
+
+    fn next(&mut self) -> Option<JsonNode<'a>> {
+        if self.current == self.end {
+            return None;
+        }
+
+        let value_index = self.current;
+        self.current = self.parser.next_sibling_index(value_index);
+
+        Some(JsonNode::from_index(self.parser, value_index))
+    }
+}
+
+// the last chunk is shorter when the length is not a multiple of size
+pub struct JsonArrayChunks<'a> {
+    iter: JsonArrayIter<'a>,
+    size: usize,
+}
+
+impl<'a> Iterator for JsonArrayChunks<'a> {
+    type Item = Vec<JsonNode<'a>>;
+
+    fn next(&mut self) -> Option<Vec<JsonNode<'a>>> {
+        let chunk: Vec<JsonNode<'a>> = self.iter.by_ref().take(self.size).collect();
+        if chunk.is_empty() {
+            None
+        } else {
+            Some(chunk)
+        }
+    }
+}
+
 impl<'a> Iterator for JsonObjectIter<'a> {
     type Item = (&'a str, JsonNode<'a>);
 
@@ -57,13 +173,18 @@ impl<'a> JsonNode<'a> {
         JsonNode { parser, index }
     }
 
+    // token index in the parser
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
     pub fn token(&self) -> &JsonToken {
         &self.parser.tokens[self.index]
     }
 
     pub fn value(&self) -> JsonValue {
         let token = self.token();
-        match token.data {
+        match token.data() {
             JsonTokenData::Value(value) => value,
             _ => panic!(),
         }
@@ -73,101 +194,296 @@ impl<'a> JsonNode<'a> {
         self.parser.get_slice(self.index)
     }
 
+    pub fn span(&self) -> (usize, usize) {
+        self.parser.span(self.index)
+    }
+
+    pub fn byte_len(&self) -> usize {
+        let (start, end) = self.span();
+        end - start
+    }
+
     pub fn get_int(&self) -> Option<i64> {
         self.parser.get_int(self.index)
     }
 
+    pub fn get_int_checked(&self) -> Result<i64, NumberError> {
+        self.parser.get_int_checked(self.index)
+    }
+
+    pub fn get_f64(&self) -> Option<f64> {
+        self.parser.get_f64(self.index)
+    }
+
+    #[cfg(feature = "decimal")]
+    pub fn get_decimal(&self) -> Option<rust_decimal::Decimal> {
+        self.parser.get_decimal(self.index)
+    }
+
+    // glTF quaternion, color... None unless exactly N numbers
+    pub fn get_f64_array<const N: usize>(&self) -> Option<[f64; N]> {
+        if self.array_len()? != N {
+            return None;
+        }
+        let mut values = [0.0; N];
+        for (value, item) in values.iter_mut().zip(self.array_iter()) {
+            *value = item.get_f64()?;
+        }
+        Some(values)
+    }
+
+    pub fn as_number(&self) -> Option<Number> {
+        self.parser.get_number(self.index)
+    }
+
+    // raw source text. a string node is compared with its double quote: "a"
+    pub fn slice_eq(&self, literal: &str) -> bool {
+        self.slice() == literal
+    }
+
+    // unescaped content of a string node: a. false for other nodes.
+    // allocates only if the string has an escape sequence
+    pub fn str_eq(&self, literal: &str) -> bool {
+        self.get_str().is_some_and(|s| s == literal)
+    }
+
+    // number without fraction and exponent. 5.0 and 5e2 are not
+    pub fn is_integer(&self) -> bool {
+        matches!(self.value(), JsonValue::Number(_, true))
+    }
+
     pub fn get_bool(&self) -> Option<bool> {
         self.parser.get_bool(self.index)
     }
 
+    pub fn get_bool_typed(&self) -> Result<bool, JsonNodeError> {
+        self.parser.get_bool_typed(self.index)
+    }
+
+    pub fn get_null_typed(&self) -> Result<(), JsonNodeError> {
+        self.parser.get_null_typed(self.index)
+    }
+
     pub fn get_string(&self) -> Option<&str> {
         self.parser.get_string(self.index)
     }
 
+    pub fn get_string_raw(&self) -> Option<&'a str> {
+        self.parser.get_string_raw(self.index)
+    }
+
+    pub fn get_string_typed(&self) -> Result<&'a str, JsonNodeError> {
+        self.parser.get_string_typed(self.index)
+    }
+
+    // unescaped. borrowed if the string has no escape sequence
+    pub fn get_str(&self) -> Option<Cow<'a, str>> {
+        unescape(self.parser.get_string(self.index)?)
+    }
+
+    // unescape once per token. later reads are served from cache
+    pub fn get_str_cached(&self, cache: &UnescapeCache) -> Option<Rc<str>> {
+        cache.get_or_insert(self.index, || self.get_str().map(Rc::from))
+    }
+
     pub fn get(&self, index: usize) -> JsonNodeResult<'a> {
         let token = self.token();
-        match token.data {
-            JsonTokenData::Value(value) => {
-                match value {
-                    JsonValue::ArrayOpen(_) => {
-                        // let close = self.parser.tokens[*close_index];
-                        let mut current = self.index + 1;
-                        for _ in 0..index {
-                            current = self.parser.next_sibling_index(current);
-                        }
-                        Ok(JsonNode {
-                            parser: self.parser,
-                            index: current,
-                        })
+        match token.data() {
+            JsonTokenData::Value(JsonValue::ArrayOpen(close_index)) => {
+                let mut current = self.index + 1;
+                for _ in 0..index {
+                    if current >= close_index {
+                        break;
                     }
-                    _ => Err(JsonNodeError {}),
+                    current = self.parser.next_sibling_index(current);
+                }
+                if current >= close_index {
+                    let len = self.array_len().unwrap_or(0);
+                    return Err(JsonNodeError::new(
+                        self,
+                        ErrorKind::IndexOutOfRange { index, len },
+                    ));
                 }
+                Ok(JsonNode {
+                    parser: self.parser,
+                    index: current,
+                })
             }
-            _ => Err(JsonNodeError {}),
+            _ => Err(JsonNodeError::new(self, ErrorKind::NotAnArray)),
         }
     }
 
-    pub fn array_len(&self) -> Option<usize> {
+    pub fn array_iter(&self) -> JsonArrayIter<'a> {
         let token = self.token();
-        match token.data {
-            JsonTokenData::Value(JsonValue::ArrayOpen(close_index)) => {
-                match self.parser.tokens[close_index].data {
-                    JsonTokenData::ArrayClose(count) => return Some(count),
-                    _ => (),
-                }
-            }
-            _ => (),
+        if let JsonTokenData::Value(JsonValue::ArrayOpen(close_index)) = token.data() {
+            return JsonArrayIter {
+                parser: self.parser,
+                current: self.index + 1,
+                end: close_index,
+            };
         }
 
-        None
+        JsonArrayIter {
+            parser: self.parser,
+            current: self.index + 1,
+            end: self.index + 1,
+        }
+    }
+
+    // panics if size is 0
+    pub fn array_chunks(&self, size: usize) -> JsonArrayChunks<'a> {
+        assert!(size > 0);
+        JsonArrayChunks {
+            iter: self.array_iter(),
+            size,
+        }
     }
 
+    // overlapping. empty if size is larger than the array
+    pub fn array_windows(&self, size: usize) -> impl Iterator<Item = Vec<JsonNode<'a>>> {
+        assert!(size > 0);
+        let items: Vec<JsonNode<'a>> = self.array_iter().collect();
+        let count = (items.len() + 1).saturating_sub(size);
+        (0..count).map(move |i| items[i..i + size].to_vec())
+    }
+
+    pub fn array_len(&self) -> Option<usize> {
+        self.parser.array_len(self.index)
+    }
+
+    // counts by walking the elements instead of reading the close token. cross-check for array_len
+    pub fn count_array_items_by_walk(&self) -> Option<usize> {
+        match self.token().data() {
+            JsonTokenData::Value(JsonValue::ArrayOpen(_)) => Some(self.array_iter().count()),
+            _ => None,
+        }
+    }
+
+    // first match. see key_with_policy for duplicated keys
     pub fn key(&self, target: &str) -> JsonNodeResult<'a> {
-        let token = self.token();
-        match token.data {
-            JsonTokenData::Value(value) => match value {
-                JsonValue::ObjectOpen(close_index) => {
-                    let mut current = self.index + 1;
-                    while current < close_index {
-                        // key
-                        let key_index = current;
-                        let value_index = self.parser.next_sibling_index(key_index);
-
-                        // value
-                        let key = self.parser.get_slice(key_index);
-                        if &key[1..key.len() - 1] == target {
-                            return Ok(JsonNode {
-                                parser: self.parser,
-                                index: value_index,
-                            });
-                        }
-
-                        current = self.parser.next_sibling_index(value_index);
-                    }
-                    // not found
-                    Err(JsonNodeError {})
+        self.key_with_policy(target, DuplicateKeyPolicy::First)
+    }
+
+    pub fn key_with_policy(&self, target: &str, policy: DuplicateKeyPolicy) -> JsonNodeResult<'a> {
+        if self.object_len().is_none() {
+            return Err(JsonNodeError::new(self, ErrorKind::NotAnObject));
+        }
+        let mut matches = self.entries(target);
+        let found = match policy {
+            DuplicateKeyPolicy::First => matches.next(),
+            DuplicateKeyPolicy::Last => matches.last(),
+            DuplicateKeyPolicy::Error => {
+                let found = matches.next();
+                if matches.next().is_some() {
+                    let kind = ErrorKind::DuplicateKey(String::from(target));
+                    return Err(JsonNodeError::new(self, kind));
                 }
-                _ => Err(JsonNodeError {}),
-            },
-            _ => Err(JsonNodeError {}),
+                found
+            }
+        };
+        match found {
+            Some((_, value_index)) => Ok(JsonNode {
+                parser: self.parser,
+                index: value_index,
+            }),
+            None => Err(JsonNodeError::new(
+                self,
+                ErrorKind::KeyNotFound(String::from(target)),
+            )),
+        }
+    }
+
+    // first array element of the kind. None for non-arrays
+    pub fn first_of_type(&self, kind: JsonKind) -> Option<JsonNode<'a>> {
+        self.array_iter().find(|item| item.value().kind() == kind)
+    }
+
+    // token indices of the key and the value
+    pub fn entry(&self, target: &str) -> Option<(usize, usize)> {
+        self.entries(target).next()
+    }
+
+    // byte range of the key token in src, quotes included. first member as entry
+    pub fn key_span(&self, target: &str) -> Option<(usize, usize)> {
+        let (key_index, _) = self.entry(target)?;
+        Some(self.parser.span(key_index))
+    }
+
+    // key and value indices of every member named target
+    fn entries<'b>(&self, target: &'b str) -> impl Iterator<Item = (usize, usize)> + 'b
+    where
+        'a: 'b,
+    {
+        let parser = self.parser;
+        // arrays have children too, but no keys
+        let mut children = match self.token().data() {
+            JsonTokenData::Value(JsonValue::ObjectOpen(_)) => Some(parser.children(self.index)),
+            _ => None,
         }
+        .into_iter()
+        .flatten();
+        core::iter::from_fn(move || loop {
+            let key_index = children.next()?;
+            let value_index = children.next()?;
+            if parser.get_string(key_index) == Some(target) {
+                return Some((key_index, value_index));
+            }
+        })
+    }
+
+    pub fn try_key(&self, target: &str) -> Option<JsonNode<'a>> {
+        self.key(target).ok()
+    }
+
+    pub fn try_get(&self, index: usize) -> Option<JsonNode<'a>> {
+        self.get(index).ok()
+    }
+
+    // "2.0" from { "asset": { "version": "2.0" } }. as written, without unescape
+    pub fn gltf_asset_version(&self) -> Option<&'a str> {
+        self.try_key("asset")?
+            .try_key("version")?
+            .get_string_typed()
+            .ok()
+    }
+
+    // default when the key is absent or the value has another type
+    pub fn get_str_or(&self, key: &str, default: &str) -> String {
+        self.key(key)
+            .ok()
+            .and_then(|v| v.get_str())
+            .map_or_else(|| String::from(default), Cow::into_owned)
+    }
+
+    pub fn get_int_or(&self, key: &str, default: i64) -> i64 {
+        self.key(key)
+            .ok()
+            .and_then(|v| v.get_int())
+            .unwrap_or(default)
+    }
+
+    pub fn get_f64_or(&self, key: &str, default: f64) -> f64 {
+        self.key(key)
+            .ok()
+            .and_then(|v| v.get_f64())
+            .unwrap_or(default)
+    }
+
+    pub fn get_bool_or(&self, key: &str, default: bool) -> bool {
+        self.key(key)
+            .ok()
+            .and_then(|v| v.get_bool())
+            .unwrap_or(default)
     }
 
-    pub fn object_iter(&self) -> JsonObjectIter {
+    pub fn object_iter(&self) -> JsonObjectIter<'a> {
         let token = self.token();
-        match token.data {
-            JsonTokenData::Value(value) => match value {
-                JsonValue::ObjectOpen(close_index) => {
-                    return JsonObjectIter {
-                        parser: self.parser,
-                        current: self.index + 1,
-                        end: close_index,
-                    }
-                }
-                _ => (),
-            },
-            _ => (),
+        if let JsonTokenData::Value(JsonValue::ObjectOpen(close_index)) = token.data() {
+            return JsonObjectIter {
+                parser: self.parser,
+                current: self.index + 1,
+                end: close_index,
+            };
         }
 
         JsonObjectIter {
@@ -177,19 +493,345 @@ impl<'a> JsonNode<'a> {
         }
     }
 
-    pub fn object_len(&self) -> Option<usize> {
-        let token = self.token();
-        match token.data {
-            JsonTokenData::Value(JsonValue::ObjectOpen(close_index)) => {
-                match self.parser.tokens[close_index].data {
-                    JsonTokenData::ObjectClose(count) => return Some(count),
-                    _ => (),
+    // dotted path and source slice of each leaf. {"a": [1]} => [("a.0", "1")].
+    // empty containers are leaves. keys are not escaped
+    pub fn flatten(&self) -> Vec<(String, &'a str)> {
+        let mut props = Vec::new();
+        self.flatten_to(String::new(), &mut props);
+        props
+    }
+
+    fn flatten_to(&self, path: String, props: &mut Vec<(String, &'a str)>) {
+        let join = |key: &str| {
+            if path.is_empty() {
+                String::from(key)
+            } else {
+                format!("{}.{}", path, key)
+            }
+        };
+        if self.array_len().unwrap_or(0) > 0 {
+            for (i, child) in self.array_iter().enumerate() {
+                child.flatten_to(join(&format!("{}", i)), props);
+            }
+        } else if self.object_len().unwrap_or(0) > 0 {
+            for (key, child) in self.object_iter() {
+                child.flatten_to(join(key), props);
+            }
+        } else {
+            props.push((path, self.parser.get_slice(self.index)));
+        }
+    }
+
+    // flatten as a map. paths can collide: {"a": [1]} and {"a": {"0": 1}} are both "a.0",
+    // and so are "a.b" and {"a": {"b": ..}}. the last leaf in document order wins
+    #[cfg(feature = "std")]
+    pub fn to_property_map(&self) -> std::collections::HashMap<String, String> {
+        self.flatten()
+            .into_iter()
+            .map(|(path, value)| (path, String::from(value)))
+            .collect()
+    }
+
+    // no whitespace. strings are re-escaped, numbers keep the source text
+    pub fn to_compact_string(&self) -> String {
+        let mut dst = String::new();
+        self.write_compact(&mut dst, false);
+        dst
+    }
+
+    // two space indent and \n line endings, whatever the source uses
+    pub fn to_pretty_string(&self) -> String {
+        let mut dst = String::new();
+        self.write_pretty(&mut dst, 0, &mut &[][..]);
+        dst
+    }
+
+    // comments from JsonParser::try_process_jsonc, each on its own line before
+    // the value that follows it. comments after the last value stay at the end
+    pub fn to_pretty_string_with_comments(&self, comments: &[JsonToken]) -> String {
+        let mut dst = String::new();
+        let mut comments = comments;
+        while let Some((comment, rest)) = comments.split_first() {
+            if comment.start() >= self.token().start() {
+                break;
+            }
+            dst.push_str(self.parser.get_comment(comment).unwrap_or(""));
+            dst.push('\n');
+            comments = rest;
+        }
+        self.write_pretty(&mut dst, 0, &mut comments);
+        for comment in comments {
+            dst.push('\n');
+            dst.push_str(self.parser.get_comment(comment).unwrap_or(""));
+        }
+        dst
+    }
+
+    // newline and indent before each comment that starts before offset
+    fn write_comments_before(
+        &self,
+        dst: &mut String,
+        indent: usize,
+        offset: usize,
+        comments: &mut &[JsonToken],
+    ) {
+        while let Some((comment, rest)) = comments.split_first() {
+            if comment.start() >= offset {
+                break;
+            }
+            write_newline(dst, indent);
+            dst.push_str(self.parser.get_comment(comment).unwrap_or(""));
+            *comments = rest;
+        }
+    }
+
+    fn write_pretty(&self, dst: &mut String, indent: usize, comments: &mut &[JsonToken]) {
+        let close_index = match self.value() {
+            JsonValue::ArrayOpen(close_index) | JsonValue::ObjectOpen(close_index) => close_index,
+            _ => return self.write_compact(dst, false),
+        };
+        let close = self.parser.tokens[close_index].start();
+        let has_comments = comments.first().is_some_and(|c| c.start() < close);
+        let mut children = self.parser.children(self.index).peekable();
+        if children.peek().is_none() && !has_comments {
+            return self.write_compact(dst, false);
+        }
+        let is_object = self.object_len().is_some();
+        dst.push(if is_object { '{' } else { '[' });
+        let mut first = true;
+        while let Some(index) = children.next() {
+            if !first {
+                dst.push(',');
+            }
+            first = false;
+            let start = self.parser.tokens[index].start();
+            // a comment is its own line, so the newline comes before it
+            self.write_comments_before(dst, indent + 1, start, comments);
+            write_newline(dst, indent + 1);
+            let child = if is_object {
+                let key = self.parser.get_string(index).unwrap_or("");
+                write_escaped(dst, &unescape(key).unwrap_or(Cow::Borrowed(key))).unwrap();
+                dst.push_str(": ");
+                JsonNode::from_index(self.parser, children.next().unwrap_or(index))
+            } else {
+                JsonNode::from_index(self.parser, index)
+            };
+            child.write_pretty(dst, indent + 1, comments);
+        }
+        self.write_comments_before(dst, indent + 1, close, comments);
+        write_newline(dst, indent);
+        dst.push(if is_object { '}' } else { ']' });
+    }
+
+    // to_compact_string with object members sorted by unescaped key, recursively.
+    // key order does not change the output. numbers keep the source text
+    pub fn to_canonical_string(&self) -> String {
+        let mut dst = String::new();
+        self.write_compact(&mut dst, true);
+        dst
+    }
+
+    fn write_compact(&self, dst: &mut String, sorted: bool) {
+        match self.value() {
+            JsonValue::String(_) => match self.get_str() {
+                Some(s) => write_escaped(dst, &s).unwrap(),
+                None => dst.push_str(self.slice()),
+            },
+            JsonValue::ArrayOpen(_) => {
+                dst.push('[');
+                for (i, child) in self.array_iter().enumerate() {
+                    if i > 0 {
+                        dst.push(',');
+                    }
+                    child.write_compact(dst, sorted);
+                }
+                dst.push(']');
+            }
+            JsonValue::ObjectOpen(_) => {
+                dst.push('{');
+                let mut members: Vec<(Cow<str>, JsonNode)> = self
+                    .object_iter()
+                    .map(|(key, child)| (unescape(key).unwrap_or(Cow::Borrowed(key)), child))
+                    .collect();
+                if sorted {
+                    members.sort_by(|a, b| a.0.cmp(&b.0));
+                }
+                for (i, (key, child)) in members.iter().enumerate() {
+                    if i > 0 {
+                        dst.push(',');
+                    }
+                    write_escaped(dst, key).unwrap();
+                    dst.push(':');
+                    child.write_compact(dst, sorted);
+                }
+                dst.push('}');
+            }
+            _ => dst.push_str(self.slice()),
+        }
+    }
+
+    // json pointer and node of this node and its descendants matching pred, in document order
+    pub fn find_all(&self, pred: impl Fn(&JsonNode) -> bool) -> Vec<(String, JsonNode<'a>)> {
+        let mut found = Vec::new();
+        self.find_all_to(String::new(), &pred, &mut found);
+        found
+    }
+
+    fn find_all_to(
+        &self,
+        pointer: String,
+        pred: &impl Fn(&JsonNode) -> bool,
+        found: &mut Vec<(String, JsonNode<'a>)>,
+    ) {
+        if pred(self) {
+            found.push((pointer.clone(), *self));
+        }
+        for (i, child) in self.array_iter().enumerate() {
+            child.find_all_to(format!("{}/{}", pointer, i), pred, found);
+        }
+        for (key, child) in self.object_iter() {
+            child.find_all_to(format!("{}/{}", pointer, pointer_token(key)), pred, found);
+        }
+    }
+
+    // counts of this node and its descendants
+    pub fn stats(&self) -> DocumentStats {
+        let mut stats = DocumentStats::default();
+        self.stats_to(1, &mut stats);
+        stats
+    }
+
+    fn stats_to(&self, depth: usize, stats: &mut DocumentStats) {
+        match self.value() {
+            JsonValue::Null() => stats.nulls += 1,
+            JsonValue::True() | JsonValue::False() => stats.booleans += 1,
+            JsonValue::Number(..) => stats.numbers += 1,
+            JsonValue::String(_) => stats.strings += 1,
+            JsonValue::ArrayOpen(_) => {
+                stats.arrays += 1;
+                stats.max_depth = stats.max_depth.max(depth);
+                for child in self.array_iter() {
+                    child.stats_to(depth + 1, stats);
+                }
+            }
+            JsonValue::ObjectOpen(_) => {
+                stats.objects += 1;
+                stats.max_depth = stats.max_depth.max(depth);
+                for (_, child) in self.object_iter() {
+                    child.stats_to(depth + 1, stats);
                 }
             }
-            _ => (),
         }
+    }
+
+    // graphviz digraph. node id is the token index
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph {\n");
+        self.write_dot(&mut dot);
+        dot.push_str("}\n");
+        dot
+    }
+
+    fn write_dot(&self, dot: &mut String) {
+        let escape = |s: &str| s.replace('\\', "\\\\").replace('"', "\\\"");
+        let label = match self.value() {
+            JsonValue::ArrayOpen(_) => String::from("[]"),
+            JsonValue::ObjectOpen(_) => String::from("{}"),
+            _ => escape(self.slice()),
+        };
+        dot.push_str(&format!("    n{} [label=\"{}\"];\n", self.index, label));
+
+        for (i, child) in self.array_iter().enumerate() {
+            dot.push_str(&format!(
+                "    n{} -> n{} [label=\"{}\"];\n",
+                self.index, child.index, i
+            ));
+            child.write_dot(dot);
+        }
+        for (key, child) in self.object_iter() {
+            dot.push_str(&format!(
+                "    n{} -> n{} [label=\"{}\"];\n",
+                self.index,
+                child.index,
+                escape(key)
+            ));
+            child.write_dot(dot);
+        }
+    }
+
+    pub fn object_len(&self) -> Option<usize> {
+        self.parser.object_len(self.index)
+    }
+
+    // [], {} and "". scalars, including 0, false and null, are never empty
+    pub fn is_empty(&self) -> bool {
+        match self.value() {
+            JsonValue::ArrayOpen(_) => self.array_len() == Some(0),
+            JsonValue::ObjectOpen(_) => self.object_len() == Some(0),
+            JsonValue::String(len) => len == 2,
+            _ => false,
+        }
+    }
+
+    // keys are unescaped. duplicate keys: the last member wins
+    pub fn to_btreemap(&self) -> Option<BTreeMap<String, JsonNode<'a>>> {
+        self.object_len()?;
+        let mut map = BTreeMap::new();
+        for (key, child) in self.object_iter() {
+            let key = unescape(key).map_or_else(|| String::from(key), Cow::into_owned);
+            map.insert(key, child);
+        }
+        Some(map)
+    }
+}
+
+fn number_eq(a: Number, b: Number) -> bool {
+    match (a, b) {
+        (Number::Int(a), Number::Int(b)) => a == b,
+        (Number::UInt(a), Number::UInt(b)) => a == b,
+        (Number::Int(_), Number::UInt(_)) | (Number::UInt(_), Number::Int(_)) => false,
+        (a, b) => number_to_f64(a) == number_to_f64(b),
+    }
+}
 
-        None
+fn number_to_f64(n: Number) -> f64 {
+    match n {
+        Number::Int(n) => n as f64,
+        Number::UInt(n) => n as f64,
+        Number::Float(n) => n,
+    }
+}
+
+// semantic equality. numbers compare by value (1 == 1.0), strings and keys after unescape,
+// object members in any order, the last of duplicate keys wins as in to_btreemap.
+// arrays are order sensitive
+pub fn json_eq(a: &JsonNode, b: &JsonNode) -> bool {
+    match (a.value(), b.value()) {
+        (JsonValue::Null(), JsonValue::Null()) => true,
+        (JsonValue::True(), JsonValue::True()) => true,
+        (JsonValue::False(), JsonValue::False()) => true,
+        (JsonValue::Number(..), JsonValue::Number(..)) => match (a.as_number(), b.as_number()) {
+            (Some(x), Some(y)) => number_eq(x, y),
+            _ => false,
+        },
+        (JsonValue::String(_), JsonValue::String(_)) => a.get_str() == b.get_str(),
+        (JsonValue::ArrayOpen(_), JsonValue::ArrayOpen(_)) => {
+            a.array_len() == b.array_len()
+                && a.array_iter()
+                    .zip(b.array_iter())
+                    .all(|(x, y)| json_eq(&x, &y))
+        }
+        (JsonValue::ObjectOpen(_), JsonValue::ObjectOpen(_)) => {
+            match (a.to_btreemap(), b.to_btreemap()) {
+                (Some(a), Some(b)) => {
+                    a.len() == b.len()
+                        && a.iter()
+                            .all(|(k, x)| b.get(k).is_some_and(|y| json_eq(x, y)))
+                }
+                _ => false,
+            }
+        }
+        _ => false,
     }
 }
 
@@ -211,7 +853,7 @@ fn slice_tests() {
 }
 
 #[test]
-fn node_tests<'a>() {
+fn node_tests() {
     {
         let parser = JsonParser::process("[1, 2, 3]");
         let array = JsonNode::new(&parser);
@@ -220,6 +862,9 @@ fn node_tests<'a>() {
         assert_eq!(Some(1), array.get(0).unwrap().get_int());
         assert_eq!(Some(2), array.get(1).unwrap().get_int());
         assert_eq!(Some(3), array.get(2).unwrap().get_int());
+
+        let values: Vec<i64> = array.array_iter().map(|v| v.get_int().unwrap()).collect();
+        assert_eq!(vec![1, 2, 3], values);
     }
 
     {
@@ -240,3 +885,645 @@ fn node_tests<'a>() {
         );
     }
 }
+
+#[test]
+fn get_str_tests() {
+    let parser = JsonParser::process(r##"["plain", "esc\"aped\n"]"##);
+    let array = JsonNode::new(&parser);
+
+    let plain = array.get(0).unwrap().get_str().unwrap();
+    assert!(matches!(plain, Cow::Borrowed("plain")));
+
+    let escaped = array.get(1).unwrap().get_str().unwrap();
+    assert!(matches!(escaped, Cow::Owned(_)));
+    assert_eq!("esc\"aped\n", escaped);
+
+    assert_eq!(None, array.get_str());
+}
+
+#[test]
+fn array_chunks_tests() {
+    let parser = JsonParser::process("[1, 2, 3, 4, 5, 6]");
+    let array = JsonNode::new(&parser);
+    let to_ints =
+        |nodes: Vec<JsonNode>| -> Vec<i64> { nodes.iter().map(|n| n.get_int().unwrap()).collect() };
+
+    let chunks: Vec<Vec<i64>> = array.array_chunks(3).map(to_ints).collect();
+    assert_eq!(vec![vec![1, 2, 3], vec![4, 5, 6]], chunks);
+
+    let chunks: Vec<Vec<i64>> = array.array_chunks(4).map(to_ints).collect();
+    assert_eq!(vec![vec![1, 2, 3, 4], vec![5, 6]], chunks);
+
+    let windows: Vec<Vec<i64>> = array.array_windows(3).map(to_ints).collect();
+    assert_eq!(
+        vec![vec![1, 2, 3], vec![2, 3, 4], vec![3, 4, 5], vec![4, 5, 6]],
+        windows
+    );
+    assert_eq!(0, array.array_windows(7).count());
+}
+
+#[test]
+fn as_number_tests() {
+    let parser = JsonParser::process(r##"[42, 18446744073709551615, -5, 2.5, 1e+2, "1"]"##);
+    let array = JsonNode::new(&parser);
+    assert_eq!(Some(Number::Int(42)), array.get(0).unwrap().as_number());
+    assert_eq!(
+        Some(Number::UInt(18446744073709551615)),
+        array.get(1).unwrap().as_number()
+    );
+    assert_eq!(Some(Number::Int(-5)), array.get(2).unwrap().as_number());
+    assert_eq!(Some(Number::Float(2.5)), array.get(3).unwrap().as_number());
+    assert_eq!(
+        Some(Number::Float(100.0)),
+        array.get(4).unwrap().as_number()
+    );
+    assert_eq!(None, array.get(5).unwrap().as_number());
+    assert_eq!(Some(2.5), array.get(3).unwrap().get_f64());
+}
+
+#[test]
+fn json_eq_tests() {
+    let eq = |a: &str, b: &str| {
+        json_eq(
+            &JsonNode::new(&JsonParser::process(a)),
+            &JsonNode::new(&JsonParser::process(b)),
+        )
+    };
+
+    assert!(eq(
+        r##"{"a": 1, "b": [true, null]}"##,
+        r##"{"b": [true, null], "a": 1}"##
+    ));
+    assert!(eq("1", "1.0"));
+    assert!(eq("100", "1e+2"));
+    assert!(eq(r##""\u0041""##, r##""A""##));
+    assert!(!eq("[1, 2]", "[2, 1]"));
+    assert!(!eq(r##"{"a": 1}"##, r##"{"a": 1, "b": 2}"##));
+    assert!(!eq("1", r##""1""##));
+    assert!(!eq("0", "false"));
+
+    // duplicate keys, either way round
+    assert!(!eq(r##"{"a": 1, "a": 1}"##, r##"{"a": 1, "b": 2}"##));
+    assert!(!eq(r##"{"a": 1, "b": 2}"##, r##"{"a": 1, "a": 1}"##));
+    assert!(eq(r##"{"a": 1, "a": 2}"##, r##"{"a": 2}"##));
+    // keys after unescape
+    assert!(eq(r##"{"c/d": 1}"##, r##"{"c\/d": 1}"##));
+    assert!(eq(r##"{"A": 1}"##, r##"{"\u0041": 1.0}"##));
+}
+
+#[test]
+fn byte_len_tests() {
+    let parser = JsonParser::process(r##"{"a": [1, 22, "x"], "b": {"c": null}}"##);
+    let root = JsonNode::new(&parser);
+    for node in [
+        root,
+        root.key("a").unwrap(),
+        root.key("a").unwrap().get(1).unwrap(),
+        root.key("a").unwrap().get(2).unwrap(),
+        root.key("b").unwrap(),
+        root.key("b").unwrap().key("c").unwrap(),
+    ] {
+        assert_eq!(node.slice().len(), node.byte_len());
+    }
+    assert_eq!(2, root.key("a").unwrap().get(1).unwrap().byte_len());
+    assert_eq!(11, root.key("b").unwrap().byte_len());
+}
+
+#[test]
+fn to_dot_tests() {
+    let parser = JsonParser::process(r##"{"a": [1, "x"], "b": true}"##);
+    let dot = JsonNode::new(&parser).to_dot();
+    assert!(dot.starts_with("digraph {\n"));
+    assert!(dot.ends_with("}\n"));
+    assert!(dot.contains("    n0 [label=\"{}\"];\n"));
+    assert!(dot.contains("    n0 -> n2 [label=\"a\"];\n"));
+    assert!(dot.contains("    n2 [label=\"[]\"];\n"));
+    assert!(dot.contains("    n2 -> n3 [label=\"0\"];\n"));
+    assert!(dot.contains("    n3 [label=\"1\"];\n"));
+    assert!(dot.contains("    n4 [label=\"\\\"x\\\"\"];\n"));
+    assert!(dot.contains("    n0 -> n7 [label=\"b\"];\n"));
+    assert!(dot.contains("    n7 [label=\"true\"];\n"));
+}
+
+#[test]
+fn flatten_tests() {
+    let parser = JsonParser::process(r##"{"a": {"b": 1, "c": [true, "x"]}, "d": [], "e": {}}"##);
+    assert_eq!(
+        vec![
+            ("a.b".to_string(), "1"),
+            ("a.c.0".to_string(), "true"),
+            ("a.c.1".to_string(), r##""x""##),
+            ("d".to_string(), "[]"),
+            ("e".to_string(), "{}"),
+        ],
+        JsonNode::new(&parser).flatten()
+    );
+
+    let parser = JsonParser::process("1");
+    assert_eq!(vec![(String::new(), "1")], JsonNode::new(&parser).flatten());
+}
+
+#[test]
+fn to_compact_string_tests() {
+    let parser = JsonParser::process(
+        r##"{ "a" : [ 1 , 2.50 , "x\u0041" ],
+            "b" : { "c" : null } }"##,
+    );
+    assert_eq!(
+        r##"{"a":[1,2.50,"xA"],"b":{"c":null}}"##,
+        JsonNode::new(&parser).to_compact_string()
+    );
+}
+
+#[test]
+fn to_btreemap_tests() {
+    let parser = JsonParser::process(r##"{"b":2,"a":1,"b":3}"##);
+    let map = JsonNode::new(&parser).to_btreemap().unwrap();
+    let keys: Vec<&str> = map.keys().map(|k| k.as_str()).collect();
+    assert_eq!(vec!["a", "b"], keys);
+    assert_eq!(Some(1), map["a"].get_int());
+    assert_eq!(Some(3), map["b"].get_int());
+
+    let parser = JsonParser::process("[1]");
+    assert!(JsonNode::new(&parser).to_btreemap().is_none());
+}
+
+#[test]
+fn get_or_tests() {
+    let parser =
+        JsonParser::process(r##"{"name": "a\"b", "count": 3, "scale": 0.5, "enabled": true}"##);
+    let node = JsonNode::new(&parser);
+
+    // present
+    assert_eq!("a\"b", node.get_str_or("name", "x"));
+    assert_eq!(3, node.get_int_or("count", 0));
+    assert_eq!(0.5, node.get_f64_or("scale", 1.0));
+    assert!(node.get_bool_or("enabled", false));
+
+    // absent
+    assert_eq!("x", node.get_str_or("missing", "x"));
+    assert_eq!(7, node.get_int_or("missing", 7));
+    assert_eq!(1.0, node.get_f64_or("missing", 1.0));
+    assert!(!node.get_bool_or("missing", false));
+
+    // wrong type
+    assert_eq!("x", node.get_str_or("count", "x"));
+    assert_eq!(7, node.get_int_or("name", 7));
+    assert_eq!(1.0, node.get_f64_or("enabled", 1.0));
+    assert!(node.get_bool_or("scale", true));
+}
+
+#[test]
+fn try_key_tests() {
+    let parser = JsonParser::process(r##"{"a": {"b": [10, {"c": true}]}}"##);
+    let node = JsonNode::new(&parser);
+
+    let c = node
+        .try_key("a")
+        .and_then(|a| a.try_key("b"))
+        .and_then(|b| b.try_get(1))
+        .and_then(|n| n.try_key("c"));
+    assert_eq!(Some(true), c.and_then(|c| c.get_bool()));
+
+    let lookup =
+        |node: JsonNode| -> Option<i64> { node.try_key("a")?.try_key("b")?.try_get(0)?.get_int() };
+    assert_eq!(Some(10), lookup(node));
+
+    assert!(node.try_key("x").and_then(|x| x.try_key("b")).is_none());
+    assert!(node.try_key("a").and_then(|a| a.try_get(0)).is_none());
+    assert!(node
+        .try_key("a")
+        .and_then(|a| a.try_key("b"))
+        .and_then(|b| b.try_get(2))
+        .is_none());
+}
+
+#[test]
+fn get_str_cached_tests() {
+    let parser = JsonParser::process(r##"["a\"b", "c", 1]"##);
+    let node = JsonNode::new(&parser);
+    let cache = UnescapeCache::new();
+
+    let first = node.get(0).unwrap().get_str_cached(&cache).unwrap();
+    assert_eq!("a\"b", &*first);
+    assert_eq!(0, cache.hits());
+    let second = node.get(0).unwrap().get_str_cached(&cache).unwrap();
+    assert_eq!(first, second);
+    assert!(Rc::ptr_eq(&first, &second));
+    assert_eq!(1, cache.hits());
+
+    assert_eq!("c", &*node.get(1).unwrap().get_str_cached(&cache).unwrap());
+    assert_eq!(None, node.get(2).unwrap().get_str_cached(&cache));
+    assert_eq!(2, cache.len());
+}
+
+#[test]
+fn to_canonical_string_tests() {
+    let a = JsonParser::process(r##"{"b": [{"y": 1, "x": 2}], "a": "s", "c": {}}"##);
+    let b = JsonParser::process(r##"{ "c": {}, "a": "s", "b": [ { "x": 2, "y": 1 } ] }"##);
+    assert_eq!(
+        r##"{"a":"s","b":[{"x":2,"y":1}],"c":{}}"##,
+        JsonNode::new(&a).to_canonical_string()
+    );
+    assert_eq!(
+        JsonNode::new(&a).to_canonical_string(),
+        JsonNode::new(&b).to_canonical_string()
+    );
+    assert_ne!(
+        JsonNode::new(&a).to_compact_string(),
+        JsonNode::new(&b).to_compact_string()
+    );
+}
+
+#[test]
+fn stats_tests() {
+    let parser = JsonParser::process(
+        r##"{
+    "asset": { "version": "2.0" },
+    "nodes": [ { "name": "a", "children": [1, 2] }, { "mesh": 0, "visible": true } ],
+    "scene": null,
+    "extras": [[[]]]
+}"##,
+    );
+    assert_eq!(
+        DocumentStats {
+            objects: 4,
+            arrays: 5,
+            strings: 2,
+            numbers: 3,
+            booleans: 1,
+            nulls: 1,
+            max_depth: 4,
+        },
+        JsonNode::new(&parser).stats()
+    );
+
+    let parser = JsonParser::process("1");
+    assert_eq!(0, JsonNode::new(&parser).stats().max_depth);
+}
+
+#[test]
+fn find_all_tests() {
+    let parser = JsonParser::process(
+        r##"{"name": "a", "values": [1, 2000, {"b/c": 5000, "d": "e"}], "count": 3}"##,
+    );
+    let node = JsonNode::new(&parser);
+
+    let strings: Vec<(String, String)> = node
+        .find_all(|n| matches!(n.value(), JsonValue::String(_)))
+        .into_iter()
+        .map(|(pointer, n)| (pointer, n.get_str().unwrap().into_owned()))
+        .collect();
+    assert_eq!(
+        vec![
+            ("/name".to_string(), "a".to_string()),
+            ("/values/2/d".to_string(), "e".to_string()),
+        ],
+        strings
+    );
+
+    let large: Vec<String> = node
+        .find_all(|n| n.get_f64().is_some_and(|n| n > 1000.0))
+        .into_iter()
+        .map(|(pointer, _)| pointer)
+        .collect();
+    assert_eq!(vec!["/values/1", "/values/2/b~1c"], large);
+
+    assert_eq!(1, node.find_all(|n| n.object_len() == Some(3)).len());
+}
+
+#[test]
+fn is_integer_tests() {
+    let parser = JsonParser::process(r##"[5, 5.0, 5e2, -12, "5"]"##);
+    let node = JsonNode::new(&parser);
+    let is_integer: Vec<bool> = node.array_iter().map(|n| n.is_integer()).collect();
+    assert_eq!(vec![true, false, false, true, false], is_integer);
+    assert_eq!(Some(Number::Int(5)), node.get(0).unwrap().as_number());
+    assert_eq!(Some(Number::Float(5.0)), node.get(1).unwrap().as_number());
+    assert_eq!(Some(Number::Float(500.0)), node.get(2).unwrap().as_number());
+}
+
+#[test]
+fn container_len_tests() {
+    let array_len = |src: &str| JsonNode::new(&JsonParser::process(src)).array_len();
+    let object_len = |src: &str| JsonNode::new(&JsonParser::process(src)).object_len();
+
+    assert_eq!(Some(0), array_len("[]"));
+    assert_eq!(Some(0), array_len("[ ]"));
+    assert_eq!(Some(0), array_len(" [\r\n\t] "));
+    assert_eq!(Some(1), array_len("[1]"));
+    assert_eq!(Some(1), array_len("[ 1 ]"));
+    assert_eq!(Some(2), array_len("[ [ ] , { } ]"));
+    assert_eq!(Some(0), object_len("{}"));
+    assert_eq!(Some(0), object_len("{ }"));
+    assert_eq!(Some(1), object_len(r##"{"a":1}"##));
+    assert_eq!(Some(1), object_len(r##"{ "a" : 1 }"##));
+    assert_eq!(Some(2), object_len(r##"{ "a" : { }, "b": [ ] }"##));
+
+    let parser = JsonParser::process(r##"[ { }, [ ], { "a": [ ] } ]"##);
+    let lens: Vec<(Option<usize>, Option<usize>)> = JsonNode::new(&parser)
+        .array_iter()
+        .map(|n| (n.array_len(), n.object_len()))
+        .collect();
+    assert_eq!(
+        vec![(None, Some(0)), (Some(0), None), (None, Some(1))],
+        lens
+    );
+}
+
+#[test]
+fn slice_eq_tests() {
+    let parser = JsonParser::process(r##"["a", 1.50, "a\nb", null]"##);
+    let node = JsonNode::new(&parser);
+
+    let a = node.get(0).unwrap();
+    assert!(a.slice_eq(r##""a""##));
+    assert!(!a.slice_eq("a"));
+    assert!(a.str_eq("a"));
+    assert!(!a.str_eq(r##""a""##));
+
+    let number = node.get(1).unwrap();
+    assert!(number.slice_eq("1.50"));
+    assert!(!number.slice_eq("1.5"));
+    assert!(!number.str_eq("1.50"));
+
+    let escaped = node.get(2).unwrap();
+    assert!(escaped.slice_eq(r##""a\nb""##));
+    assert!(escaped.str_eq("a\nb"));
+
+    assert!(node.get(3).unwrap().slice_eq("null"));
+}
+
+#[test]
+fn to_pretty_string_tests() {
+    let expected = r##"{
+  "a": [
+    1,
+    "x\ny"
+  ],
+  "b": {},
+  "c": {
+    "d": []
+  }
+}"##;
+    let lf = r##"{"a": [1, "x\ny"],
+"b": { },
+"c": {"d": []}}"##;
+    let crlf = lf.replace('\n', "\r\n");
+    assert_eq!(
+        expected,
+        JsonNode::new(&JsonParser::process(lf)).to_pretty_string()
+    );
+    assert_eq!(
+        expected,
+        JsonNode::new(&JsonParser::process(&crlf)).to_pretty_string()
+    );
+    assert_eq!(
+        "1",
+        JsonNode::new(&JsonParser::process("1")).to_pretty_string()
+    );
+}
+
+#[test]
+fn entry_tests() {
+    let parser = JsonParser::process(r##"{"a": 1, "b": {"c": [2]}, "d": true}"##);
+    let node = JsonNode::new(&parser);
+    let (key_index, value_index) = node.entry("b").unwrap();
+    assert_eq!(r##""b""##, parser.get_slice(key_index));
+    assert_eq!(r##"{"c": [2]}"##, parser.get_slice(value_index));
+    let (key_index, value_index) = node.entry("d").unwrap();
+    assert_eq!(r##""d""##, parser.get_slice(key_index));
+    assert_eq!(Some(true), parser.get_bool(value_index));
+    assert_eq!(None, node.entry("c"));
+    assert_eq!(None, node.key("a").unwrap().entry("a"));
+}
+
+#[test]
+fn key_span_tests() {
+    let src = "{\"a\": 1,\n  \"b\": {\"c\": [2]}}";
+    let parser = JsonParser::process(src);
+    let node = JsonNode::new(&parser);
+    assert_eq!(Some((1, 4)), node.key_span("a"));
+    let (start, end) = node.key_span("b").unwrap();
+    assert_eq!(r##""b""##, &src[start..end]);
+    let (start, end) = node.key("b").unwrap().key_span("c").unwrap();
+    assert_eq!(r##""c""##, &src[start..end]);
+    assert_eq!(None, node.key_span("c"));
+}
+
+#[test]
+fn first_of_type_tests() {
+    let parser = JsonParser::process(r##"[1, "x", {"a":1}, {"b":2}, true]"##);
+    let node = JsonNode::new(&parser);
+    let object = node.first_of_type(JsonKind::Object).unwrap();
+    assert_eq!(r##"{"a":1}"##, object.to_string());
+    assert_eq!(
+        "x",
+        node.first_of_type(JsonKind::String)
+            .unwrap()
+            .get_string()
+            .unwrap()
+    );
+    assert_eq!(
+        Some(true),
+        node.first_of_type(JsonKind::Bool).unwrap().get_bool()
+    );
+    assert!(node.first_of_type(JsonKind::Null).is_none());
+    assert!(object.first_of_type(JsonKind::Number).is_none());
+}
+
+#[test]
+fn key_with_policy_tests() {
+    let parser = JsonParser::process(r##"{"a":1,"b":0,"a":2}"##);
+    let node = JsonNode::new(&parser);
+    let get = |policy| {
+        node.key_with_policy("a", policy)
+            .ok()
+            .and_then(|v| v.get_int())
+    };
+    assert_eq!(Some(1), get(DuplicateKeyPolicy::First));
+    assert_eq!(Some(2), get(DuplicateKeyPolicy::Last));
+    assert_eq!(None, get(DuplicateKeyPolicy::Error));
+    assert_eq!(Some(1), node.key("a").unwrap().get_int());
+    assert_eq!(
+        Some(0),
+        node.key_with_policy("b", DuplicateKeyPolicy::Error)
+            .unwrap()
+            .get_int()
+    );
+    assert!(node.key_with_policy("c", DuplicateKeyPolicy::Last).is_err());
+}
+
+#[test]
+fn parser_len_tests() {
+    let parser = JsonParser::process(r##"{"a": [1, [], {"b": 2}], "c": {}}"##);
+    for index in 0..parser.tokens.len() {
+        let node = JsonNode::from_index(&parser, index);
+        assert_eq!(node.array_len(), parser.array_len(index));
+        assert_eq!(node.object_len(), parser.object_len(index));
+    }
+    assert_eq!(Some(2), parser.object_len(0));
+    assert_eq!(None, parser.array_len(0));
+    let (_, a) = JsonNode::new(&parser).entry("a").unwrap();
+    assert_eq!(Some(3), parser.array_len(a));
+}
+
+#[test]
+fn count_array_items_by_walk_tests() {
+    for src in [
+        "[]",
+        "[ ]",
+        "[1]",
+        "[1, [2, 3], {\"a\": [4]}, \"x\"]",
+        "[[[]], [], null]",
+    ] {
+        let parser = JsonParser::process(src);
+        for index in 0..parser.tokens.len() {
+            let node = JsonNode::from_index(&parser, index);
+            assert_eq!(
+                node.array_len(),
+                node.count_array_items_by_walk(),
+                "{}",
+                src
+            );
+        }
+    }
+    let parser = JsonParser::process("{}");
+    assert_eq!(None, JsonNode::new(&parser).count_array_items_by_walk());
+}
+
+#[test]
+fn node_error_tests() {
+    let parser = JsonParser::process(r##"{"a": [1, 2], "b": 3, "b": 4}"##);
+    let root = JsonNode::new(&parser);
+    let kind = |result: JsonNodeResult| result.err().unwrap().kind;
+    let a = root.key("a").unwrap();
+    assert_eq!(ErrorKind::NotAnArray, kind(root.get(0)));
+    assert_eq!(ErrorKind::NotAnObject, kind(a.key("x")));
+    assert_eq!(
+        ErrorKind::IndexOutOfRange { index: 2, len: 2 },
+        kind(a.get(2))
+    );
+    assert_eq!(ErrorKind::KeyNotFound("x".to_string()), kind(root.key("x")));
+    assert_eq!(
+        ErrorKind::DuplicateKey("b".to_string()),
+        kind(root.key_with_policy("b", DuplicateKeyPolicy::Error))
+    );
+
+    let error = a.get(5).err().unwrap();
+    assert_eq!(a.index(), error.index);
+    assert_eq!(
+        format!("node {}: index 5 is out of range for length 2", a.index()),
+        error.to_string()
+    );
+    assert_eq!(
+        "node 0: key x is not found",
+        root.key("x").err().unwrap().to_string()
+    );
+}
+
+#[test]
+fn get_f64_array_tests() {
+    let parser = JsonParser::process(r##"[[1, 0.5, 0, 1], [1, 2, 3], [1, "0", 0, 1], {}]"##);
+    let root = JsonNode::new(&parser);
+    let color = root.get(0).unwrap();
+    assert_eq!(Some([1.0, 0.5, 0.0, 1.0]), color.get_f64_array::<4>());
+    assert_eq!(None, color.get_f64_array::<3>());
+    assert_eq!(None, root.get(1).unwrap().get_f64_array::<4>());
+    assert_eq!(Some([1.0, 2.0, 3.0]), root.get(1).unwrap().get_f64_array());
+    assert_eq!(None, root.get(2).unwrap().get_f64_array::<4>());
+    assert_eq!(None, root.get(3).unwrap().get_f64_array::<0>());
+}
+
+#[test]
+fn to_pretty_string_with_comments_tests() {
+    let src = r##"// leading
+{"a": 1, /* before b */ "b": [1, // one
+2], "c": [ /* empty */ ]}
+// trailing"##;
+    let (parser, comments) = JsonParser::try_process_jsonc(src).unwrap();
+    let pretty = JsonNode::new(&parser).to_pretty_string_with_comments(&comments);
+    let expected = r##"// leading
+{
+  "a": 1,
+  /* before b */
+  "b": [
+    1,
+    // one
+    2
+  ],
+  "c": [
+    /* empty */
+  ]
+}
+// trailing"##;
+    assert_eq!(expected, pretty);
+
+    // round trip
+    let (parser, comments) = JsonParser::try_process_jsonc(&pretty).unwrap();
+    assert_eq!(
+        expected,
+        JsonNode::new(&parser).to_pretty_string_with_comments(&comments)
+    );
+    assert_eq!(
+        JsonNode::new(&parser).to_pretty_string(),
+        JsonNode::new(&JsonParser::process(r##"{"a": 1, "b": [1, 2], "c": []}"##))
+            .to_pretty_string()
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn to_property_map_tests() {
+    let parser = JsonParser::process(
+        r##"{"window": {"size": [640, 480], "title": "main"}, "window.title": "dotted"}"##,
+    );
+    let map = JsonNode::new(&parser).to_property_map();
+    assert_eq!(3, map.len());
+    assert_eq!("640", map["window.size.0"]);
+    assert_eq!("480", map["window.size.1"]);
+    assert_eq!(r##""dotted""##, map["window.title"]);
+    assert!(!map.contains_key("window.size"));
+}
+
+#[test]
+fn is_empty_tests() {
+    let parser = JsonParser::process(r##"[[], {}, "", [0], {"a": 1}, " ", 0, false, null]"##);
+    let empty: Vec<bool> = JsonNode::new(&parser)
+        .array_iter()
+        .map(|v| v.is_empty())
+        .collect();
+    assert_eq!(
+        vec![true, true, true, false, false, false, false, false, false],
+        empty
+    );
+}
+
+#[test]
+fn gltf_asset_version_tests() {
+    let parser = JsonParser::process(
+        r##"{"asset": {"generator": "x", "version": "2.0"}, "scenes": [{"nodes": [0]}]}"##,
+    );
+    assert_eq!(Some("2.0"), JsonNode::new(&parser).gltf_asset_version());
+
+    let parser = JsonParser::process(r##"{"scenes": []}"##);
+    assert_eq!(None, JsonNode::new(&parser).gltf_asset_version());
+    let parser = JsonParser::process(r##"{"asset": {"version": 2}}"##);
+    assert_eq!(None, JsonNode::new(&parser).gltf_asset_version());
+}
+
+#[test]
+fn get_string_raw_tests() {
+    let parser = JsonParser::process(r##"{"k\"ey": "a\nb", "n": 1}"##);
+    let node = JsonNode::new(&parser);
+    let value = node.key("k\\\"ey").unwrap();
+    assert_eq!(Some(r##""a\nb""##), value.get_string_raw());
+    assert_eq!(Some(r##"a\nb"##), value.get_string());
+    assert_eq!("a\nb", value.get_str().unwrap());
+    assert_eq!(None, node.key("n").unwrap().get_string_raw());
+    assert_eq!(None, node.get_string_raw());
+
+    let parser = JsonParser::process("\"\"");
+    assert_eq!(Some("\"\""), JsonNode::new(&parser).get_string_raw());
+    assert_eq!(Some(""), JsonNode::new(&parser).get_string());
+}
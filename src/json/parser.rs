@@ -1,33 +1,74 @@
-use std::fmt;
+use super::node::{ErrorKind, JsonNodeError};
+use super::string::try_unescape;
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::num::IntErrorKind;
 
-struct PeekIt<'a> {
-    it: std::str::CharIndices<'a>,
+pub(crate) struct PeekIt<'a> {
+    src: &'a str,
+    it: core::str::CharIndices<'a>,
     last: Option<(usize, char)>,
 }
 
 impl<'a> PeekIt<'a> {
-    fn new(it: std::str::CharIndices) -> PeekIt {
-        PeekIt { it, last: None }
+    pub(crate) fn new(it: core::str::CharIndices<'a>) -> PeekIt<'a> {
+        PeekIt {
+            src: it.as_str(),
+            it,
+            last: None,
+        }
+    }
+
+    // whole source. offsets of it are relative to this
+    pub(crate) fn src(&self) -> &'a str {
+        self.src
     }
 
-    fn next(&mut self) {
+    pub(crate) fn next(&mut self) {
         self.last = self.it.next();
     }
 
-    fn peek(&mut self) -> Option<(usize, char)> {
+    pub(crate) fn peek(&mut self) -> Option<(usize, char)> {
         self.last
     }
+
+    // ascii whitespace bytes are skipped without decoding chars
+    pub(crate) fn skip_whitespace(&mut self) {
+        while let Some((_, c)) = self.last {
+            if !is_whitespace(c) {
+                return;
+            }
+            let n = self
+                .it
+                .as_str()
+                .bytes()
+                .take_while(|b| matches!(b, b' ' | b'\t' | b'\r' | b'\n'))
+                .count();
+            if n > 0 {
+                self.it.nth(n - 1);
+            }
+            self.last = self.it.next();
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy)]
+// only these four separate tokens. unicode spaces are not json whitespace
+pub(crate) fn is_whitespace(c: char) -> bool {
+    matches!(c, ' ' | '\t' | '\r' | '\n')
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum JsonValue {
     Null(),
     True(),
     False(),
-    Number(usize),     // byte len
-    String(usize),     // byte len. include double quote
-    ArrayOpen(usize),  // close index.
-    ObjectOpen(usize), // close index.
+    Number(usize, bool), // byte len, is_integer (no fraction and no exponent)
+    String(usize),       // byte len. include double quote
+    ArrayOpen(usize),    // close index.
+    ObjectOpen(usize),   // close index.
 }
 
 impl fmt::Display for JsonValue {
@@ -36,7 +77,7 @@ impl fmt::Display for JsonValue {
             JsonValue::Null() => write!(f, "null"),
             JsonValue::True() => write!(f, "true"),
             JsonValue::False() => write!(f, "false"),
-            JsonValue::Number(len) => write!(f, "number[{}]", len),
+            JsonValue::Number(len, _) => write!(f, "number[{}]", len),
             JsonValue::String(len) => write!(f, "string[{}]", len),
             JsonValue::ArrayOpen(_) => write!(f, "["),
             JsonValue::ObjectOpen(_) => write!(f, "{{"),
@@ -44,19 +85,161 @@ impl fmt::Display for JsonValue {
     }
 }
 
-#[derive(Debug, Clone, Copy)]
+// JsonValue without payload. true and false are both Bool
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JsonKind {
+    Null,
+    Bool,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+impl JsonValue {
+    pub fn kind(&self) -> JsonKind {
+        match self {
+            JsonValue::Null() => JsonKind::Null,
+            JsonValue::True() | JsonValue::False() => JsonKind::Bool,
+            JsonValue::Number(..) => JsonKind::Number,
+            JsonValue::String(_) => JsonKind::String,
+            JsonValue::ArrayOpen(_) => JsonKind::Array,
+            JsonValue::ObjectOpen(_) => JsonKind::Object,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Number {
+    Int(i64),
+    UInt(u64), // larger than i64::MAX
+    Float(f64),
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Number::Int(n) => write!(f, "{}", n),
+            Number::UInt(n) => write!(f, "{}", n),
+            // Debug keeps ".0" so the value reads back as Float
+            Number::Float(n) if n.is_finite() => write!(f, "{:?}", n),
+            Number::Float(_) => write!(f, "null"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum NumberError {
+    NotANumber,
+    NotAnInteger, // has fraction or exponent
+    Overflow,     // integer out of i64 range
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub enum JsonTokenData {
     Value(JsonValue),
     Comma(),
     Colon(),
     ArrayClose(usize),  // count
     ObjectClose(usize), // count
+    Comment(usize),     // byte len. only from try_process_jsonc, never in JsonParser::tokens
 }
 
-#[derive(Debug, Clone, Copy)]
+// packed to 12 bytes. offsets, lengths and indices are u32,
+// so the source is limited to u32::MAX bytes
+#[derive(Clone, Copy, PartialEq)]
 pub struct JsonToken {
-    start: usize,
-    pub data: JsonTokenData,
+    start: u32,
+    payload: u32,
+    tag: u8,
+}
+
+const TAG_NULL: u8 = 0;
+const TAG_TRUE: u8 = 1;
+const TAG_FALSE: u8 = 2;
+const TAG_NUMBER: u8 = 3;
+const TAG_STRING: u8 = 4;
+const TAG_ARRAY_OPEN: u8 = 5;
+const TAG_OBJECT_OPEN: u8 = 6;
+const TAG_COMMA: u8 = 7;
+const TAG_COLON: u8 = 8;
+const TAG_ARRAY_CLOSE: u8 = 9;
+const TAG_OBJECT_CLOSE: u8 = 10;
+const TAG_INTEGER: u8 = 11;
+const TAG_COMMENT: u8 = 12;
+
+impl JsonToken {
+    pub(crate) fn new(start: usize, data: JsonTokenData) -> JsonToken {
+        let (tag, payload) = match data {
+            JsonTokenData::Value(JsonValue::Null()) => (TAG_NULL, 0),
+            JsonTokenData::Value(JsonValue::True()) => (TAG_TRUE, 0),
+            JsonTokenData::Value(JsonValue::False()) => (TAG_FALSE, 0),
+            JsonTokenData::Value(JsonValue::Number(len, false)) => (TAG_NUMBER, len),
+            JsonTokenData::Value(JsonValue::Number(len, true)) => (TAG_INTEGER, len),
+            JsonTokenData::Value(JsonValue::String(len)) => (TAG_STRING, len),
+            JsonTokenData::Value(JsonValue::ArrayOpen(close_index)) => {
+                (TAG_ARRAY_OPEN, close_index)
+            }
+            JsonTokenData::Value(JsonValue::ObjectOpen(close_index)) => {
+                (TAG_OBJECT_OPEN, close_index)
+            }
+            JsonTokenData::Comma() => (TAG_COMMA, 0),
+            JsonTokenData::Colon() => (TAG_COLON, 0),
+            JsonTokenData::ArrayClose(count) => (TAG_ARRAY_CLOSE, count),
+            JsonTokenData::ObjectClose(count) => (TAG_OBJECT_CLOSE, count),
+            JsonTokenData::Comment(len) => (TAG_COMMENT, len),
+        };
+        JsonToken {
+            start: start as u32,
+            payload: payload as u32,
+            tag,
+        }
+    }
+
+    pub(crate) fn start(&self) -> usize {
+        self.start as usize
+    }
+
+    pub fn data(&self) -> JsonTokenData {
+        let payload = self.payload as usize;
+        match self.tag {
+            TAG_NULL => JsonTokenData::Value(JsonValue::Null()),
+            TAG_TRUE => JsonTokenData::Value(JsonValue::True()),
+            TAG_FALSE => JsonTokenData::Value(JsonValue::False()),
+            TAG_NUMBER => JsonTokenData::Value(JsonValue::Number(payload, false)),
+            TAG_INTEGER => JsonTokenData::Value(JsonValue::Number(payload, true)),
+            TAG_STRING => JsonTokenData::Value(JsonValue::String(payload)),
+            TAG_ARRAY_OPEN => JsonTokenData::Value(JsonValue::ArrayOpen(payload)),
+            TAG_OBJECT_OPEN => JsonTokenData::Value(JsonValue::ObjectOpen(payload)),
+            TAG_COMMA => JsonTokenData::Comma(),
+            TAG_COLON => JsonTokenData::Colon(),
+            TAG_ARRAY_CLOSE => JsonTokenData::ArrayClose(payload),
+            TAG_COMMENT => JsonTokenData::Comment(payload),
+            _ => JsonTokenData::ObjectClose(payload),
+        }
+    }
+}
+
+// cache blob: magic, version, source len, source hash, token count, then 9 bytes per token.
+// bump the version when the token layout changes
+const TOKEN_CACHE_MAGIC: &[u8; 4] = b"JPTK";
+const TOKEN_CACHE_VERSION: u8 = 1;
+const TOKEN_CACHE_HEADER: usize = 4 + 1 + 4 + 4 + 4;
+
+// FNV-1a. detects a cache made from another source
+fn source_hash(src: &str) -> u32 {
+    src.bytes().fold(0x811c_9dc5, |hash, b| {
+        (hash ^ b as u32).wrapping_mul(0x0100_0193)
+    })
+}
+
+impl fmt::Debug for JsonToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("JsonToken")
+            .field("start", &self.start())
+            .field("data", &self.data())
+            .finish()
+    }
 }
 
 impl fmt::Display for JsonTokenData {
@@ -67,31 +250,226 @@ impl fmt::Display for JsonTokenData {
             JsonTokenData::ArrayClose(_) => write!(f, "]"),
             JsonTokenData::ObjectClose(_) => write!(f, "}}"),
             JsonTokenData::Value(value) => write!(f, "{}", value),
+            JsonTokenData::Comment(len) => write!(f, "comment[{}]", len),
         }
     }
 }
 
+// well-formed json. the tokens are dropped
+pub fn is_valid(src: &str) -> bool {
+    JsonParser::try_process(src).is_ok()
+}
+
+// 1 based line and column (in chars) of a byte offset. \r\n, \n and \r are one line break
+pub fn line_col(src: &str, offset: usize) -> (usize, usize) {
+    let mut line = 1;
+    let mut col = 1;
+    let mut it = src[..offset].chars().peekable();
+    while let Some(c) = it.next() {
+        match c {
+            '\r' => {
+                if it.peek() == Some(&'\n') {
+                    it.next();
+                }
+                line += 1;
+                col = 1;
+            }
+            '\n' => {
+                line += 1;
+                col = 1;
+            }
+            _ => col += 1,
+        }
+    }
+    (line, col)
+}
+
 #[derive(Debug, Clone)]
-enum ParseError {
+pub enum ParseError {
     Eof(),
     Unknown(usize, char),
-    Value(usize, JsonValue),
+    Value(usize, JsonValue, &'static str), // offset, value, what was expected instead
+    TooLarge(usize),                       // source byte len
+    TrailingContent(usize),
+    StringTooLong(usize),       // offset of the opening quote
+    TooManyElements(usize),     // offset of the first value over the limit
+    InvalidEscape(usize, char), // offset of the backslash, char after it
+    InvalidUtf16(usize),        // byte offset in the utf-16 input
+    MismatchedClose {
+        open_offset: usize,
+        open_char: char,
+        close_offset: usize,
+        close_char: char,
+    },
+    #[cfg(feature = "std")]
+    Io(std::io::ErrorKind),
 }
 type ParseResult = Result<JsonToken, ParseError>;
 
+impl ParseError {
+    // byte offset in the source. eof is the source len
+    pub fn offset(&self, src: &str) -> Option<usize> {
+        match *self {
+            ParseError::Eof() => Some(src.len()),
+            ParseError::Unknown(i, _) | ParseError::Value(i, ..) => Some(i),
+            ParseError::TrailingContent(i)
+            | ParseError::StringTooLong(i)
+            | ParseError::TooManyElements(i)
+            | ParseError::InvalidEscape(i, _) => Some(i),
+            ParseError::MismatchedClose { close_offset, .. } => Some(close_offset),
+            _ => None,
+        }
+    }
+
+    // "line:column: message", the source line and a caret under the column.
+    // lines end with \n whatever the source uses
+    pub fn render(&self, src: &str) -> String {
+        use core::fmt::Write;
+        let offset = match self.offset(src) {
+            Some(offset) => offset,
+            None => return format!("{}\n", self),
+        };
+        let (line, col) = line_col(src, offset);
+        let start = src[..offset].rfind(['\r', '\n']).map_or(0, |i| i + 1);
+        let end = src[offset..]
+            .find(['\r', '\n'])
+            .map_or(src.len(), |i| offset + i);
+        let mut dst = String::new();
+        writeln!(dst, "{}:{}: {}", line, col, self).unwrap();
+        writeln!(dst, "{}", &src[start..end]).unwrap();
+        writeln!(dst, "{}^", " ".repeat(col - 1)).unwrap();
+        dst
+    }
+}
+
 impl fmt::Display for ParseError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             ParseError::Eof() => write!(f, "eof"),
             ParseError::Unknown(i, c) => write!(f, "'{}' at {} is unknown", c, i),
-            ParseError::Value(i, value) => write!(f, "{} at {} is invalid", value, i),
+            ParseError::Value(i, value, expected) => {
+                write!(f, "{} at {} is invalid (expected {})", value, i, expected)
+            }
+            ParseError::TooLarge(len) => write!(f, "{} bytes is too large", len),
+            ParseError::TrailingContent(i) => write!(f, "trailing content at {}", i),
+            ParseError::StringTooLong(i) => write!(f, "string at {} is too long", i),
+            ParseError::TooManyElements(i) => write!(f, "too many elements at {}", i),
+            ParseError::InvalidEscape(i, c) => write!(f, "invalid escape \\{} at {}", c, i),
+            ParseError::InvalidUtf16(i) => write!(f, "invalid utf-16 at {}", i),
+            ParseError::MismatchedClose {
+                open_offset,
+                open_char,
+                close_offset,
+                close_char,
+            } => write!(
+                f,
+                "`{}` at {} closed by `{}` at {}",
+                open_char, open_offset, close_char, close_offset
+            ),
+            #[cfg(feature = "std")]
+            ParseError::Io(kind) => write!(f, "{}", kind),
         }
     }
 }
 
 pub struct JsonParser<'a> {
-    pub src: &'a str,
-    pub tokens: Vec<JsonToken>,
+    pub src: Cow<'a, str>,
+    pub tokens: Cow<'a, [JsonToken]>,
+}
+
+// where the tokenizer writes tokens
+trait TokenBuffer: core::ops::IndexMut<usize, Output = JsonToken> {
+    fn push(&mut self, token: JsonToken);
+    fn len(&self) -> usize;
+}
+
+impl TokenBuffer for Vec<JsonToken> {
+    fn push(&mut self, token: JsonToken) {
+        Vec::push(self, token)
+    }
+
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+}
+
+#[cfg(feature = "bumpalo")]
+impl TokenBuffer for bumpalo::collections::Vec<'_, JsonToken> {
+    fn push(&mut self, token: JsonToken) {
+        bumpalo::collections::Vec::push(self, token)
+    }
+
+    fn len(&self) -> usize {
+        bumpalo::collections::Vec::len(self)
+    }
+}
+
+// tokens between progress callbacks
+const PROGRESS_INTERVAL: usize = 1024;
+
+// Vec that reports the offset of every PROGRESS_INTERVAL-th token.
+// other entry points use a plain Vec and pay nothing
+struct ProgressTokens<F: FnMut(usize, usize)> {
+    tokens: Vec<JsonToken>,
+    total: usize,
+    progress: F,
+}
+
+impl<F: FnMut(usize, usize)> core::ops::Index<usize> for ProgressTokens<F> {
+    type Output = JsonToken;
+
+    fn index(&self, index: usize) -> &JsonToken {
+        &self.tokens[index]
+    }
+}
+
+impl<F: FnMut(usize, usize)> core::ops::IndexMut<usize> for ProgressTokens<F> {
+    fn index_mut(&mut self, index: usize) -> &mut JsonToken {
+        &mut self.tokens[index]
+    }
+}
+
+impl<F: FnMut(usize, usize)> TokenBuffer for ProgressTokens<F> {
+    fn push(&mut self, token: JsonToken) {
+        self.tokens.push(token);
+        if self.tokens.len().is_multiple_of(PROGRESS_INTERVAL) {
+            (self.progress)(token.start(), self.total);
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.tokens.len()
+    }
+}
+
+// limits for untrusted input. the default is unlimited
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ParseOptions {
+    // bytes between the quotes, escapes as written
+    pub max_string_len: Option<usize>,
+    // value tokens in the whole document, object keys included
+    pub max_elements: Option<usize>,
+    // unescape every string while tokenizing. otherwise bad escapes surface on get_str
+    pub strict_escapes: bool,
+}
+
+struct Tokenizer<T: TokenBuffer> {
+    tokens: T,
+    options: ParseOptions,
+    elements: usize,
+    // Some for jsonc. comments are skipped like whitespace and recorded here
+    comments: Option<Vec<JsonToken>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Utf16Endian {
+    Little,
+    Big,
+}
+
+// parser that owns its source
+pub struct JsonParserOwned {
+    parser: JsonParser<'static>,
 }
 
 fn get_char(it: &mut PeekIt, expected: char) -> Result<usize, ParseError> {
@@ -107,41 +485,294 @@ fn get_char(it: &mut PeekIt, expected: char) -> Result<usize, ParseError> {
     }
 }
 
+fn mismatched_close(
+    open_offset: usize,
+    open_char: char,
+    close_offset: usize,
+    close_char: char,
+) -> ParseError {
+    ParseError::MismatchedClose {
+        open_offset,
+        open_char,
+        close_offset,
+        close_char,
+    }
+}
+
+// token fields are u32
+fn check_len(src: &str) -> Result<(), ParseError> {
+    if src.len() > u32::MAX as usize {
+        return Err(ParseError::TooLarge(src.len()));
+    }
+    Ok(())
+}
+
+// byte ranges of the elements of a top-level array, whitespace trimmed.
+// only brackets and strings are scanned, None if src is not an array
+fn array_element_ranges(src: &str) -> Option<Vec<(usize, usize)>> {
+    let bytes = src.as_bytes();
+    let open = src.len() - src.trim_start_matches(is_whitespace).len();
+    let close = src.trim_end_matches(is_whitespace).len().checked_sub(1)?;
+    if open >= close || bytes[open] != b'[' || bytes[close] != b']' {
+        return None;
+    }
+    if src[open + 1..close].trim_matches(is_whitespace).is_empty() {
+        return Some(Vec::new());
+    }
+
+    let mut ranges = Vec::new();
+    let mut push = |start: usize, end: usize| {
+        let element = &src[start..end];
+        let start = start + element.len() - element.trim_start_matches(is_whitespace).len();
+        ranges.push((start, start + element.trim_matches(is_whitespace).len()));
+    };
+    let mut depth = 0;
+    let mut start = open + 1;
+    let mut i = open + 1;
+    while i < close {
+        match bytes[i] {
+            b'"' => {
+                i += 1;
+                while i < close && bytes[i] != b'"' {
+                    if bytes[i] == b'\\' {
+                        i += 1;
+                    }
+                    i += 1;
+                }
+            }
+            b'[' | b'{' => depth += 1,
+            b']' | b'}' => depth -= 1,
+            b',' if depth == 0 => {
+                push(start, i);
+                start = i + 1;
+            }
+            _ => (),
+        }
+        i += 1;
+    }
+    push(start, close);
+    Some(ranges)
+}
+
+// Unknown for the char at offset, Eof at the end
+fn unexpected_at(src: &str, offset: usize) -> ParseError {
+    match src[offset..].chars().next() {
+        Some(c) => ParseError::Unknown(offset, c),
+        None => ParseError::Eof(),
+    }
+}
+
+// -?(0|[1-9][0-9]*)(\.[0-9]+)?([eE][+-]?[0-9]+)?
+fn check_strict_number(src: &str, start: usize, number: &str) -> Result<(), ParseError> {
+    let bytes = number.as_bytes();
+    let digits = |from: usize| {
+        bytes[from..]
+            .iter()
+            .take_while(|b| b.is_ascii_digit())
+            .count()
+    };
+
+    let mut i = if bytes[0] == b'-' { 1 } else { 0 };
+    let int_len = digits(i);
+    if int_len == 0 {
+        return Err(unexpected_at(src, start + i));
+    }
+    if int_len > 1 && bytes[i] == b'0' {
+        // leading zero
+        return Err(unexpected_at(src, start + i + 1));
+    }
+    i += int_len;
+    if i < bytes.len() && bytes[i] == b'.' {
+        i += 1;
+        let frac_len = digits(i);
+        if frac_len == 0 {
+            return Err(unexpected_at(src, start + i));
+        }
+        i += frac_len;
+    }
+    if i < bytes.len() && (bytes[i] == b'e' || bytes[i] == b'E') {
+        i += 1;
+        if i < bytes.len() && (bytes[i] == b'+' || bytes[i] == b'-') {
+            i += 1;
+        }
+        if digits(i) == 0 {
+            return Err(unexpected_at(src, start + i));
+        }
+    }
+    // the tokenizer consumes a . or e without digits after the token text
+    let end = start + number.len();
+    if src[end..].starts_with(['.', 'e', 'E', '+', '-']) {
+        return Err(unexpected_at(src, end));
+    }
+    Ok(())
+}
+
+// string with double quote
+fn check_strict_string(start: usize, string: &str) -> Result<(), ParseError> {
+    let mut it = string.char_indices().skip(1);
+    while let Some((i, c)) = it.next() {
+        if (c as u32) < 0x20 {
+            return Err(ParseError::Unknown(start + i, c));
+        }
+        if c == '\\' {
+            let escape = match it.next() {
+                Some((i, c)) => (i, c),
+                None => return Err(ParseError::Eof()),
+            };
+            match escape.1 {
+                '"' | '\\' | '/' | 'b' | 'f' | 'n' | 'r' | 't' => (),
+                'u' => {
+                    for _ in 0..4 {
+                        match it.next() {
+                            Some((_, c)) if c.is_ascii_hexdigit() => (),
+                            Some((i, c)) => return Err(ParseError::Unknown(start + i, c)),
+                            None => return Err(ParseError::Eof()),
+                        }
+                    }
+                }
+                c => return Err(ParseError::Unknown(start + escape.0, c)),
+            }
+        }
+    }
+    Ok(())
+}
+
+// only whitespace after the root value
+fn check_trailing(it: &mut PeekIt) -> Result<(), ParseError> {
+    it.skip_whitespace();
+    match it.peek() {
+        Some((i, _)) => Err(ParseError::TrailingContent(i)),
+        None => Ok(()),
+    }
+}
+
+// clears tokens and fills them from src. tokens keep their capacity
+fn tokenize_into(
+    src: &str,
+    tokens: &mut Vec<JsonToken>,
+    options: ParseOptions,
+) -> Result<(), ParseError> {
+    check_len(src)?;
+    tokens.clear();
+    let mut tokenizer = Tokenizer {
+        tokens: core::mem::take(tokens),
+        options,
+        elements: 0,
+        comments: None,
+    };
+    let mut it = PeekIt::new(src.char_indices());
+    it.next();
+    let result = tokenizer
+        .parse(&mut it)
+        .and_then(|_| check_trailing(&mut it));
+    *tokens = tokenizer.tokens;
+    result
+}
+
 fn is_digit(c: char) -> bool {
-    match c {
-        '0'..='9' => true,
+    c.is_ascii_digit()
+}
+
+fn is_delimiter(c: char) -> bool {
+    is_whitespace(c) || matches!(c, ',' | ':' | '[' | ']' | '{' | '}')
+}
+
+// same byte length, so offsets still refer to the original source
+fn fill(src: &mut String, start: usize, end: usize, head: &str) {
+    let mut patch = String::from(head);
+    while patch.len() < end - start {
+        patch.push(' ');
+    }
+    src.replace_range(start..end, &patch);
+}
+
+// patch src so that parsing can continue past the error. false if no patch is known
+fn recover(src: &mut String, error: &ParseError) -> bool {
+    match *error {
+        ParseError::Unknown(i, c) => {
+            let end = src[i..].find(is_delimiter).map_or(src.len(), |p| i + p);
+            match src[..i].char_indices().next_back() {
+                Some((_, prev)) if !is_delimiter(prev) => {
+                    // broken word such as `tru`
+                    let start = src[..i]
+                        .char_indices()
+                        .rev()
+                        .find(|(_, c)| is_delimiter(*c))
+                        .map_or(0, |(p, c)| p + c.len_utf8());
+                    fill(src, start, end, "0");
+                }
+                _ if c == ',' || c == ':' => fill(src, i, i + 1, ""),
+                _ if c == ']' || c == '}' => {
+                    // trailing comma
+                    match src[..i]
+                        .char_indices()
+                        .rev()
+                        .find(|(_, c)| !is_whitespace(*c))
+                    {
+                        Some((p, ',')) => fill(src, p, p + 1, ""),
+                        _ => return false,
+                    }
+                }
+                _ => fill(src, i, end, "0"),
+            }
+            true
+        }
+        ParseError::Value(i, value, _) => {
+            let len = match value {
+                JsonValue::Null() | JsonValue::True() => 4,
+                JsonValue::False() => 5,
+                JsonValue::Number(n, _) | JsonValue::String(n) => n,
+                _ => 1,
+            };
+            fill(src, i, i + len, "");
+            true
+        }
+        ParseError::TrailingContent(i) => {
+            let end = src.len();
+            fill(src, i, end, "");
+            true
+        }
+        ParseError::MismatchedClose {
+            open_char,
+            close_offset,
+            ..
+        } => {
+            let close = if open_char == '[' { "]" } else { "}" };
+            fill(src, close_offset, close_offset + 1, close);
+            true
+        }
         _ => false,
     }
 }
 
 impl JsonToken {
-    fn get_null_token(it: &mut PeekIt, start: usize) -> ParseResult {
+    pub(crate) fn get_null_token(it: &mut PeekIt, start: usize) -> ParseResult {
         get_char(it, 'u')?;
         it.next();
         get_char(it, 'l')?;
         it.next();
         get_char(it, 'l')?;
         it.next();
-        Ok(JsonToken {
+        Ok(JsonToken::new(
             start,
-            data: JsonTokenData::Value(JsonValue::Null()),
-        })
+            JsonTokenData::Value(JsonValue::Null()),
+        ))
     }
 
-    fn get_true_token(it: &mut PeekIt, start: usize) -> ParseResult {
+    pub(crate) fn get_true_token(it: &mut PeekIt, start: usize) -> ParseResult {
         get_char(it, 'r')?;
         it.next();
         get_char(it, 'u')?;
         it.next();
         get_char(it, 'e')?;
         it.next();
-        Ok(JsonToken {
+        Ok(JsonToken::new(
             start,
-            data: JsonTokenData::Value(JsonValue::True()),
-        })
+            JsonTokenData::Value(JsonValue::True()),
+        ))
     }
 
-    fn get_false_token(it: &mut PeekIt, start: usize) -> ParseResult {
+    pub(crate) fn get_false_token(it: &mut PeekIt, start: usize) -> ParseResult {
         get_char(it, 'a')?;
         it.next();
         get_char(it, 'l')?;
@@ -150,15 +781,16 @@ impl JsonToken {
         it.next();
         get_char(it, 'e')?;
         it.next();
-        Ok(JsonToken {
+        Ok(JsonToken::new(
             start,
-            data: JsonTokenData::Value(JsonValue::False()),
-        })
+            JsonTokenData::Value(JsonValue::False()),
+        ))
     }
 
-    fn get_number_token(it: &mut PeekIt, start: usize) -> ParseResult {
+    pub(crate) fn get_number_token(it: &mut PeekIt, start: usize) -> ParseResult {
         let mut digit = start;
         let mut last = ' ';
+        let mut is_integer = true;
 
         while let Some((i, c)) = it.peek() {
             if is_digit(c) {
@@ -171,6 +803,7 @@ impl JsonToken {
         }
 
         if last == '.' {
+            is_integer = false;
             it.next();
             while let Some((i, c)) = it.peek() {
                 if is_digit(c) {
@@ -184,57 +817,64 @@ impl JsonToken {
         }
 
         if last == 'E' || last == 'e' {
+            is_integer = false;
             it.next();
-            if let Some((i, c)) = it.peek() {
-                if c == '+' || c == '-' {
+            // sign is optional
+            match it.peek() {
+                Some((_, '+')) | Some((_, '-')) => it.next(),
+                Some((_, c)) if is_digit(c) => (),
+                Some((i, c)) => return Err(ParseError::Unknown(i, c)),
+                None => return Err(ParseError::Eof()),
+            }
+            while let Some((i, c)) = it.peek() {
+                if is_digit(c) {
                     it.next();
-                    while let Some((i, c)) = it.peek() {
-                        if is_digit(c) {
-                            it.next();
-                            digit = i;
-                            continue;
-                        }
-                        break;
-                    }
-                } else {
-                    return Err(ParseError::Unknown(i, c));
+                    digit = i;
+                    continue;
                 }
-            } else {
-                return Err(ParseError::Eof());
+                break;
             }
         }
 
-        Ok(JsonToken {
+        Ok(JsonToken::new(
             start,
-            data: JsonTokenData::Value(JsonValue::Number(digit + 1 - start)),
-        })
+            JsonTokenData::Value(JsonValue::Number(digit + 1 - start, is_integer)),
+        ))
     }
 
-    fn get_string_token(it: &mut PeekIt, start: usize) -> ParseResult {
+    pub(crate) fn get_string_token(it: &mut PeekIt, start: usize) -> ParseResult {
         while let Some((i, c)) = it.peek() {
             it.next();
+            if c == '\\' {
+                // skip escaped char
+                it.next();
+                continue;
+            }
             if c == '"' {
-                return Ok(JsonToken {
+                return Ok(JsonToken::new(
                     start,
-                    data: JsonTokenData::Value(JsonValue::String(i + 1 - start)),
-                });
+                    JsonTokenData::Value(JsonValue::String(i + 1 - start)),
+                ));
             }
         }
         Err(ParseError::Eof())
     }
 }
 
-impl<'a> JsonParser<'a> {
-    fn get_array_token(&mut self, it: &mut PeekIt) -> Result<usize, ParseError> {
+impl<T: TokenBuffer> Tokenizer<T> {
+    fn get_array_token(&mut self, it: &mut PeekIt, open: usize) -> Result<usize, ParseError> {
         {
             // close or key
             let token = self.parse(it)?;
-            match token.data {
+            match token.data() {
                 JsonTokenData::ArrayClose(_) => return Ok(0),
-                JsonTokenData::ObjectClose(_) => return Err(ParseError::Unknown(token.start, '}')),
+                JsonTokenData::ObjectClose(_) => {
+                    return Err(mismatched_close(open, '[', token.start(), '}'))
+                }
                 JsonTokenData::Value(_) => (), // continue
-                JsonTokenData::Comma() => return Err(ParseError::Unknown(token.start, ',')),
-                JsonTokenData::Colon() => return Err(ParseError::Unknown(token.start, ':')),
+                JsonTokenData::Comma() => return Err(ParseError::Unknown(token.start(), ',')),
+                JsonTokenData::Colon() => return Err(ParseError::Unknown(token.start(), ':')),
+                JsonTokenData::Comment(_) => unreachable!(),
             };
         }
 
@@ -243,22 +883,23 @@ impl<'a> JsonParser<'a> {
             // comma or close
             {
                 let token = self.parse(it)?;
-                match token.data {
+                match token.data() {
                     JsonTokenData::ArrayClose(_) => return Ok(count),
                     JsonTokenData::ObjectClose(_) => {
-                        return Err(ParseError::Unknown(token.start, '}'))
+                        return Err(mismatched_close(open, '[', token.start(), '}'))
                     }
                     JsonTokenData::Value(value) => {
-                        return Err(ParseError::Value(token.start, value))
+                        return Err(ParseError::Value(token.start(), value, "',' or ']'"))
                     }
                     JsonTokenData::Comma() => {
                         if count > 0 {
-                            () // continue
+                            // continue
                         } else {
-                            return Err(ParseError::Unknown(token.start, ','));
+                            return Err(ParseError::Unknown(token.start(), ','));
                         }
                     }
-                    JsonTokenData::Colon() => return Err(ParseError::Unknown(token.start, ':')),
+                    JsonTokenData::Colon() => return Err(ParseError::Unknown(token.start(), ':')),
+                    JsonTokenData::Comment(_) => unreachable!(),
                 };
             }
 
@@ -268,16 +909,17 @@ impl<'a> JsonParser<'a> {
             // must value
             {
                 let token = self.parse(it)?;
-                match token.data {
+                match token.data() {
                     JsonTokenData::ArrayClose(_) => {
-                        return Err(ParseError::Unknown(token.start, ']'))
+                        return Err(ParseError::Unknown(token.start(), ']'))
                     }
                     JsonTokenData::ObjectClose(_) => {
-                        return Err(ParseError::Unknown(token.start, '}'))
+                        return Err(mismatched_close(open, '[', token.start(), '}'))
                     }
                     JsonTokenData::Value(_) => (), // continue
-                    JsonTokenData::Comma() => return Err(ParseError::Unknown(token.start, ',')),
-                    JsonTokenData::Colon() => return Err(ParseError::Unknown(token.start, ':')),
+                    JsonTokenData::Comma() => return Err(ParseError::Unknown(token.start(), ',')),
+                    JsonTokenData::Colon() => return Err(ParseError::Unknown(token.start(), ':')),
+                    JsonTokenData::Comment(_) => unreachable!(),
                 };
             }
         }
@@ -287,37 +929,48 @@ impl<'a> JsonParser<'a> {
         // :
         {
             let token = self.parse(it)?;
-            match token.data {
-                JsonTokenData::ArrayClose(_) => return Err(ParseError::Unknown(token.start, ']')),
-                JsonTokenData::ObjectClose(_) => return Err(ParseError::Unknown(token.start, '}')),
-                JsonTokenData::Value(value) => return Err(ParseError::Value(token.start, value)),
-                JsonTokenData::Comma() => return Err(ParseError::Unknown(token.start, ',')),
+            match token.data() {
+                JsonTokenData::ArrayClose(_) => {
+                    return Err(ParseError::Unknown(token.start(), ']'))
+                }
+                JsonTokenData::ObjectClose(_) => {
+                    return Err(ParseError::Unknown(token.start(), '}'))
+                }
+                JsonTokenData::Value(value) => {
+                    return Err(ParseError::Value(token.start(), value, "':'"))
+                }
+                JsonTokenData::Comma() => return Err(ParseError::Unknown(token.start(), ',')),
                 JsonTokenData::Colon() => (), // continue
+                JsonTokenData::Comment(_) => unreachable!(),
             }
         }
         // value
         {
             let token = self.parse(it)?;
-            match token.data {
-                JsonTokenData::ArrayClose(_) => return Err(ParseError::Unknown(token.start, ']')),
-                JsonTokenData::ObjectClose(_) => return Err(ParseError::Unknown(token.start, '}')),
+            match token.data() {
+                JsonTokenData::ArrayClose(_) => Err(ParseError::Unknown(token.start(), ']')),
+                JsonTokenData::ObjectClose(_) => Err(ParseError::Unknown(token.start(), '}')),
                 JsonTokenData::Value(_) => Ok(token),
-                JsonTokenData::Comma() => return Err(ParseError::Unknown(token.start, ',')),
-                JsonTokenData::Colon() => return Err(ParseError::Unknown(token.start, ':')),
+                JsonTokenData::Comma() => Err(ParseError::Unknown(token.start(), ',')),
+                JsonTokenData::Colon() => Err(ParseError::Unknown(token.start(), ':')),
+                JsonTokenData::Comment(_) => unreachable!(),
             }
         }
     }
 
-    fn get_object_token(&mut self, it: &mut PeekIt) -> Result<usize, ParseError> {
+    fn get_object_token(&mut self, it: &mut PeekIt, open: usize) -> Result<usize, ParseError> {
         {
             // close or key
             let token = self.parse(it)?;
-            match token.data {
-                JsonTokenData::ArrayClose(_) => return Err(ParseError::Unknown(token.start, ']')),
+            match token.data() {
+                JsonTokenData::ArrayClose(_) => {
+                    return Err(mismatched_close(open, '{', token.start(), ']'))
+                }
                 JsonTokenData::ObjectClose(_) => return Ok(0),
                 JsonTokenData::Value(_) => (), // continue
-                JsonTokenData::Comma() => return Err(ParseError::Unknown(token.start, ',')),
-                JsonTokenData::Colon() => return Err(ParseError::Unknown(token.start, ':')),
+                JsonTokenData::Comma() => return Err(ParseError::Unknown(token.start(), ',')),
+                JsonTokenData::Colon() => return Err(ParseError::Unknown(token.start(), ':')),
+                JsonTokenData::Comment(_) => unreachable!(),
             };
             self.colon_value(it)?;
         }
@@ -327,18 +980,19 @@ impl<'a> JsonParser<'a> {
             {
                 // comma or close
                 let token = self.parse(it)?;
-                match token.data {
+                match token.data() {
                     JsonTokenData::ArrayClose(_) => {
-                        return Err(ParseError::Unknown(token.start, ']'))
+                        return Err(mismatched_close(open, '{', token.start(), ']'))
                     }
                     JsonTokenData::ObjectClose(_) => return Ok(count),
                     JsonTokenData::Value(value) => {
-                        return Err(ParseError::Value(token.start, value))
+                        return Err(ParseError::Value(token.start(), value, "',' or '}'"))
                     }
                     JsonTokenData::Comma() => {
-                        () // continue
+                        // continue
                     }
-                    JsonTokenData::Colon() => return Err(ParseError::Unknown(token.start, ':')),
+                    JsonTokenData::Colon() => return Err(ParseError::Unknown(token.start(), ':')),
+                    JsonTokenData::Comment(_) => unreachable!(),
                 };
             }
             // increment
@@ -346,28 +1000,87 @@ impl<'a> JsonParser<'a> {
             // key
             {
                 let token = self.parse(it)?;
-                match token.data {
+                match token.data() {
                     JsonTokenData::ArrayClose(_) => {
-                        return Err(ParseError::Unknown(token.start, ']'))
+                        return Err(mismatched_close(open, '{', token.start(), ']'))
                     }
                     JsonTokenData::ObjectClose(_) => {
-                        return Err(ParseError::Unknown(token.start, '}'))
+                        return Err(ParseError::Unknown(token.start(), '}'))
                     }
                     JsonTokenData::Value(_) => (),
-                    JsonTokenData::Comma() => return Err(ParseError::Unknown(token.start, ',')),
-                    JsonTokenData::Colon() => return Err(ParseError::Unknown(token.start, ':')),
+                    JsonTokenData::Comma() => return Err(ParseError::Unknown(token.start(), ',')),
+                    JsonTokenData::Colon() => return Err(ParseError::Unknown(token.start(), ':')),
+                    JsonTokenData::Comment(_) => unreachable!(),
                 };
             }
             self.colon_value(it)?;
         }
     }
 
+    // "// ..." to the end of line or "/* ... */". it is at the first '/'
+    fn comment(&mut self, it: &mut PeekIt, start: usize) -> Result<(), ParseError> {
+        it.next();
+        let end = match it.peek() {
+            Some((_, '/')) => {
+                let mut end = start + 1;
+                loop {
+                    match it.peek() {
+                        Some((_, '\r')) | Some((_, '\n')) | None => break end,
+                        Some((i, c)) => {
+                            end = i + c.len_utf8();
+                            it.next();
+                        }
+                    }
+                }
+            }
+            Some((_, '*')) => {
+                it.next();
+                let mut star = false;
+                loop {
+                    match it.peek() {
+                        Some((i, '/')) if star => {
+                            it.next();
+                            break i + 1;
+                        }
+                        Some((_, c)) => {
+                            star = c == '*';
+                            it.next();
+                        }
+                        None => return Err(ParseError::Eof()),
+                    }
+                }
+            }
+            Some((i, c)) => return Err(ParseError::Unknown(i, c)),
+            None => return Err(ParseError::Eof()),
+        };
+        if let Some(comments) = &mut self.comments {
+            comments.push(JsonToken::new(start, JsonTokenData::Comment(end - start)));
+        }
+        Ok(())
+    }
+
     fn parse(&mut self, it: &mut PeekIt) -> ParseResult {
         while let Some((i, c)) = it.peek() {
-            it.next();
-            if c.is_whitespace() {
+            if is_whitespace(c) {
+                it.skip_whitespace();
+                continue;
+            }
+            if c == '/' && self.comments.is_some() {
+                self.comment(it, i)?;
                 continue;
             }
+            it.next();
+
+            if matches!(c, 'n' | 't' | 'f' | '0'..='9' | '-' | '"' | '[' | '{') {
+                self.elements += 1;
+                if self
+                    .options
+                    .max_elements
+                    .is_some_and(|max| self.elements > max)
+                {
+                    return Err(ParseError::TooManyElements(i));
+                }
+            }
 
             return match c {
                 'n' => {
@@ -392,80 +1105,77 @@ impl<'a> JsonParser<'a> {
                 }
                 '"' => {
                     let token = JsonToken::get_string_token(it, i)?;
+                    if let JsonTokenData::Value(JsonValue::String(len)) = token.data() {
+                        if self.options.max_string_len.is_some_and(|max| len - 2 > max) {
+                            return Err(ParseError::StringTooLong(i));
+                        }
+                        if self.options.strict_escapes {
+                            try_unescape(&it.src()[i + 1..i + len - 1]).map_err(|e| match e {
+                                ParseError::InvalidEscape(at, c) => {
+                                    ParseError::InvalidEscape(i + 1 + at, c)
+                                }
+                                e => e,
+                            })?;
+                        }
+                    }
                     self.tokens.push(token);
                     Ok(token)
                 }
-                ',' => Ok(JsonToken {
-                    start: i,
-                    data: JsonTokenData::Comma(),
-                }),
+                ',' => Ok(JsonToken::new(i, JsonTokenData::Comma())),
                 '[' => {
                     // tmp open
                     let open_index = self.tokens.len();
-                    self.tokens.push(JsonToken {
-                        start: i,
-                        data: JsonTokenData::Value(JsonValue::ArrayOpen(open_index + 1)),
-                    });
-                    let item_count = self.get_array_token(it)?;
+                    self.tokens.push(JsonToken::new(
+                        i,
+                        JsonTokenData::Value(JsonValue::ArrayOpen(open_index + 1)),
+                    ));
+                    let item_count = self.get_array_token(it, i)?;
                     let close_index = self.tokens.len() - 1;
 
                     // update open
-                    let token = JsonToken {
-                        start: i,
-                        data: JsonTokenData::Value(JsonValue::ArrayOpen(close_index)),
-                    };
+                    let token =
+                        JsonToken::new(i, JsonTokenData::Value(JsonValue::ArrayOpen(close_index)));
                     self.tokens[open_index] = token;
 
                     // update close
-                    self.tokens[close_index] = JsonToken {
-                        start: self.tokens[close_index].start,
-                        data: JsonTokenData::ArrayClose(item_count),
-                    };
+                    self.tokens[close_index] = JsonToken::new(
+                        self.tokens[close_index].start(),
+                        JsonTokenData::ArrayClose(item_count),
+                    );
 
                     Ok(token)
                 }
                 ']' => {
-                    let token = JsonToken {
-                        start: i,
-                        data: JsonTokenData::ArrayClose(0),
-                    };
+                    let token = JsonToken::new(i, JsonTokenData::ArrayClose(0));
                     self.tokens.push(token);
                     Ok(token)
                 }
-                ':' => Ok(JsonToken {
-                    start: i,
-                    data: JsonTokenData::Colon(),
-                }),
+                ':' => Ok(JsonToken::new(i, JsonTokenData::Colon())),
                 '{' => {
                     // tmp open
                     let open_index = self.tokens.len();
-                    self.tokens.push(JsonToken {
-                        start: i,
-                        data: JsonTokenData::Value(JsonValue::ObjectOpen(open_index + 1)),
-                    });
-                    let item_count = self.get_object_token(it)?;
+                    self.tokens.push(JsonToken::new(
+                        i,
+                        JsonTokenData::Value(JsonValue::ObjectOpen(open_index + 1)),
+                    ));
+                    let item_count = self.get_object_token(it, i)?;
                     let close_index = self.tokens.len() - 1;
 
                     // update open
-                    let token = JsonToken {
-                        start: i,
-                        data: JsonTokenData::Value(JsonValue::ObjectOpen(close_index)),
-                    };
+                    let token =
+                        JsonToken::new(i, JsonTokenData::Value(JsonValue::ObjectOpen(close_index)));
                     self.tokens[open_index] = token;
 
                     // update close
-                    self.tokens[close_index] = JsonToken {
-                        start: self.tokens[close_index].start,
-                        data: JsonTokenData::ObjectClose(item_count),
-                    };
+                    self.tokens[close_index] = JsonToken::new(
+                        self.tokens[close_index].start(),
+                        JsonTokenData::ObjectClose(item_count),
+                    );
 
                     Ok(token)
                 }
                 '}' => {
-                    let token = JsonToken {
-                        start: i,
-                        data: JsonTokenData::ObjectClose(0),
-                    };
+                    let token = JsonToken::new(i, JsonTokenData::ObjectClose(0));
                     self.tokens.push(token);
                     Ok(token)
                 }
@@ -474,95 +1184,628 @@ impl<'a> JsonParser<'a> {
         }
         Err(ParseError::Eof())
     }
+}
+
+impl<'a> JsonParser<'a> {
+    pub fn try_process(src: &str) -> Result<JsonParser<'_>, ParseError> {
+        JsonParser::try_process_with(src, ParseOptions::default())
+    }
 
-    pub fn process(src: &str) -> JsonParser {
-        let mut parser = JsonParser {
-            src: src,
+    pub fn try_process_with(
+        src: &str,
+        options: ParseOptions,
+    ) -> Result<JsonParser<'_>, ParseError> {
+        let mut tokens = Vec::new();
+        tokenize_into(src, &mut tokens, options)?;
+        Ok(JsonParser {
+            src: Cow::Borrowed(src),
+            tokens: Cow::Owned(tokens),
+        })
+    }
+
+    // json with // and /* */ comments. the comments are returned separately in document
+    // order, so tokens are the same as for the source without comments
+    pub fn try_process_jsonc(src: &str) -> Result<(JsonParser<'_>, Vec<JsonToken>), ParseError> {
+        check_len(src)?;
+        let mut tokenizer = Tokenizer {
             tokens: Vec::new(),
+            options: ParseOptions::default(),
+            elements: 0,
+            comments: Some(Vec::new()),
         };
-
-        let mut it = PeekIt::new(parser.src.char_indices());
+        let mut it = PeekIt::new(src.char_indices());
         it.next();
-        match parser.parse(&mut it) {
-            Ok(_) => {
-                return parser;
-                // let end = parser.value_end(i, &value);
-                // println!(
-                //     r##""{}"[{}..{}] => {}"##,
-                //     parser.src,
-                //     i,
-                //     end,
-                //     &parser.src[i..end]
-                // )
+        tokenizer.parse(&mut it)?;
+        // trailing comments
+        loop {
+            it.skip_whitespace();
+            match it.peek() {
+                Some((i, '/')) => tokenizer.comment(&mut it, i)?,
+                Some((i, _)) => return Err(ParseError::TrailingContent(i)),
+                None => break,
             }
-            Err(error) => println!("{} => {}", parser.src, error),
-            _ => print!("unknown"),
         }
-
-        panic!()
+        let parser = JsonParser {
+            src: Cow::Borrowed(src),
+            tokens: Cow::Owned(tokenizer.tokens),
+        };
+        Ok((parser, tokenizer.comments.unwrap_or_default()))
     }
 
-    pub fn next_sibling_index(&self, index: usize) -> usize {
-        let token = self.tokens[index];
-        match token.data {
-            JsonTokenData::Value(value) => match value {
-                JsonValue::ArrayOpen(close_index) => close_index + 1,
-                JsonValue::ObjectOpen(close_index) => close_index + 1,
-                _ => index + 1,
+    // progress(bytes_done, total) is called every PROGRESS_INTERVAL tokens
+    // and once with (total, total) on success
+    pub fn try_process_with_progress(
+        src: &str,
+        progress: impl FnMut(usize, usize),
+    ) -> Result<JsonParser<'_>, ParseError> {
+        check_len(src)?;
+        let mut tokenizer = Tokenizer {
+            tokens: ProgressTokens {
+                tokens: Vec::new(),
+                total: src.len(),
+                progress,
             },
-            _ => panic!(),
-        }
+            options: ParseOptions::default(),
+            elements: 0,
+            comments: None,
+        };
+        let mut it = PeekIt::new(src.char_indices());
+        it.next();
+        tokenizer.parse(&mut it)?;
+        check_trailing(&mut it)?;
+        let mut tokens = tokenizer.tokens;
+        (tokens.progress)(src.len(), src.len());
+        Ok(JsonParser {
+            src: Cow::Borrowed(src),
+            tokens: Cow::Owned(tokens.tokens),
+        })
     }
 
-    fn value_len(&self, value: JsonValue) -> usize {
-        match value {
-            JsonValue::Null() => 4,
-            JsonValue::True() => 4,
-            JsonValue::False() => 5,
-            JsonValue::Number(n) => n,
-            JsonValue::String(n) => n,
-            _ => panic!(),
+    pub fn process_with_progress(src: &str, progress: impl FnMut(usize, usize)) -> JsonParser<'_> {
+        match JsonParser::try_process_with_progress(src, progress) {
+            Ok(parser) => parser,
+            Err(error) => panic!("{} => {}", src, error),
         }
     }
 
-    pub fn get_slice(&self, index: usize) -> &str {
-        let token = &self.tokens[index];
-        let end = match token.data {
-            JsonTokenData::Value(value) => match value {
-                JsonValue::ArrayOpen(close_index) => {
-                    let close = self.tokens[close_index];
-                    close.start + 1
-                }
-                JsonValue::ObjectOpen(close_index) => {
-                    let close = self.tokens[close_index];
-                    close.start + 1
-                }
-                _ => token.start + self.value_len(value),
+    // tokens are allocated in bump. reset bump between batches to reuse the memory
+    #[cfg(feature = "bumpalo")]
+    pub fn try_process_in(
+        src: &'a str,
+        bump: &'a bumpalo::Bump,
+    ) -> Result<JsonParser<'a>, ParseError> {
+        check_len(src)?;
+        let mut tokenizer = Tokenizer {
+            tokens: bumpalo::collections::Vec::new_in(bump),
+            options: ParseOptions::default(),
+            elements: 0,
+            comments: None,
+        };
+        let mut it = PeekIt::new(src.char_indices());
+        it.next();
+        tokenizer.parse(&mut it)?;
+        check_trailing(&mut it)?;
+        Ok(JsonParser {
+            src: Cow::Borrowed(src),
+            tokens: Cow::Borrowed(tokenizer.tokens.into_bump_slice()),
+        })
+    }
+
+    // byte ranges of the elements of a top-level array without tokenizing them,
+    // so each element can be parsed on demand. src is not validated.
+    // None if src is not an array
+    pub fn element_ranges(src: &str) -> Option<Vec<(usize, usize)>> {
+        array_element_ranges(src)
+    }
+
+    // elements of a top-level array are tokenized in parallel.
+    // the result is the same as try_process. on error or for other documents
+    // it falls back to try_process
+    #[cfg(feature = "rayon")]
+    pub fn try_process_array_parallel(src: &str) -> Result<JsonParser<'_>, ParseError> {
+        use rayon::prelude::*;
+
+        let ranges = match array_element_ranges(src) {
+            Some(ranges) => ranges,
+            None => return JsonParser::try_process(src),
+        };
+        let elements: Vec<Option<Vec<JsonToken>>> = ranges
+            .par_iter()
+            .map(|&(start, end)| {
+                let element = &src[start..end];
+                let parser = JsonParser::try_process(element).ok()?;
+                Some(parser.tokens.into_owned())
+            })
+            .collect();
+
+        let mut tokens = Vec::new();
+        tokens.push(JsonToken::new(0, JsonTokenData::Comma())); // open placeholder
+        for (element, &(start, _)) in elements.iter().zip(&ranges) {
+            let element = match element {
+                Some(element) => element,
+                None => return JsonParser::try_process(src),
+            };
+            let index_base = tokens.len();
+            tokens.extend(element.iter().map(|token| {
+                let data = match token.data() {
+                    JsonTokenData::Value(JsonValue::ArrayOpen(close_index)) => {
+                        JsonTokenData::Value(JsonValue::ArrayOpen(close_index + index_base))
+                    }
+                    JsonTokenData::Value(JsonValue::ObjectOpen(close_index)) => {
+                        JsonTokenData::Value(JsonValue::ObjectOpen(close_index + index_base))
+                    }
+                    data => data,
+                };
+                JsonToken::new(token.start() + start, data)
+            }));
+        }
+        let open = src.len() - src.trim_start_matches(is_whitespace).len();
+        let close = src.trim_end_matches(is_whitespace).len() - 1;
+        tokens[0] = JsonToken::new(
+            open,
+            JsonTokenData::Value(JsonValue::ArrayOpen(tokens.len())),
+        );
+        tokens.push(JsonToken::new(
+            close,
+            JsonTokenData::ArrayClose(ranges.len()),
+        ));
+
+        Ok(JsonParser {
+            src: Cow::Borrowed(src),
+            tokens: Cow::Owned(tokens),
+        })
+    }
+
+    #[cfg(feature = "rayon")]
+    pub fn process_array_parallel(src: &str) -> JsonParser<'_> {
+        match JsonParser::try_process_array_parallel(src) {
+            Ok(parser) => parser,
+            Err(error) => panic!("{} => {}", src, error),
+        }
+    }
+
+    pub fn process(src: &str) -> JsonParser<'_> {
+        match JsonParser::try_process(src) {
+            Ok(parser) => parser,
+            Err(error) => panic!("{} => {}", src, error),
+        }
+    }
+
+    // RFC 8259. in addition to try_process, rejects leading zeros, numbers without
+    // digits after - . e, control chars and unknown escapes in strings
+    pub fn try_process_strict(src: &str) -> Result<JsonParser<'_>, ParseError> {
+        let parser = JsonParser::try_process(src)?;
+        for (index, token) in parser.tokens.iter().enumerate() {
+            match token.data() {
+                JsonTokenData::Value(JsonValue::Number(..)) => {
+                    check_strict_number(src, token.start(), parser.get_slice(index))?
+                }
+                JsonTokenData::Value(JsonValue::String(_)) => {
+                    check_strict_string(token.start(), parser.get_slice(index))?
+                }
+                _ => (),
+            }
+        }
+        Ok(parser)
+    }
+
+    pub fn process_strict(src: &str) -> JsonParser<'_> {
+        match JsonParser::try_process_strict(src) {
+            Ok(parser) => parser,
+            Err(error) => panic!("{} => {}", src, error),
+        }
+    }
+
+    // collect every error instead of stopping at the first one.
+    // after an error the source is patched and parsed again:
+    // a broken word becomes 0, a stray , or : and a trailing comma are removed,
+    // an unexpected value and trailing content are removed and a mismatched close is swapped.
+    // patches keep the byte length, so offsets match the original source.
+    // the parser is None if an error has no patch (eof)
+    pub fn process_recovering(src: &str) -> (Option<JsonParser<'_>>, Vec<ParseError>) {
+        let mut errors = Vec::new();
+        let error = match JsonParser::try_process(src) {
+            Ok(parser) => return (Some(parser), errors),
+            Err(error) => error,
+        };
+
+        let mut patched = String::from(src);
+        let mut error = Some(error);
+        while let Some(e) = error.take() {
+            let patchable = errors.len() < src.len() && recover(&mut patched, &e);
+            errors.push(e);
+            if !patchable {
+                return (None, errors);
+            }
+            match JsonParser::try_process(&patched) {
+                Ok(parser) => {
+                    let tokens = parser.tokens.into_owned();
+                    let parser = JsonParser {
+                        src: Cow::Owned(patched),
+                        tokens: Cow::Owned(tokens),
+                    };
+                    return (Some(parser), errors);
+                }
+                Err(e) => error = Some(e),
+            }
+        }
+        (None, errors)
+    }
+
+    #[cfg(feature = "std")]
+    pub fn from_reader(mut r: impl std::io::Read) -> Result<JsonParserOwned, ParseError> {
+        let mut src = String::new();
+        r.read_to_string(&mut src)
+            .map_err(|error| ParseError::Io(error.kind()))?;
+        JsonParserOwned::from_string(src)
+    }
+
+    // endian is used when bytes has no BOM. a BOM is dropped and decides the endian
+    pub fn try_process_utf16(
+        bytes: &[u8],
+        endian: Utf16Endian,
+    ) -> Result<JsonParserOwned, ParseError> {
+        let (bytes, endian, offset) = match bytes {
+            [0xFF, 0xFE, rest @ ..] => (rest, Utf16Endian::Little, 2),
+            [0xFE, 0xFF, rest @ ..] => (rest, Utf16Endian::Big, 2),
+            _ => (bytes, endian, 0),
+        };
+        if bytes.len() % 2 != 0 {
+            return Err(ParseError::InvalidUtf16(offset + bytes.len() - 1));
+        }
+        let units = bytes.chunks_exact(2).map(|pair| match endian {
+            Utf16Endian::Little => u16::from_le_bytes([pair[0], pair[1]]),
+            Utf16Endian::Big => u16::from_be_bytes([pair[0], pair[1]]),
+        });
+        let mut src = String::with_capacity(bytes.len() / 2);
+        let mut position = offset;
+        for c in core::char::decode_utf16(units) {
+            let c = c.map_err(|_| ParseError::InvalidUtf16(position))?;
+            position += c.len_utf16() * 2;
+            src.push(c);
+        }
+        JsonParserOwned::from_string(src)
+    }
+
+    pub fn process_utf16(bytes: &[u8], endian: Utf16Endian) -> JsonParserOwned {
+        match JsonParser::try_process_utf16(bytes, endian) {
+            Ok(parser) => parser,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    // see deserialize_tokens
+    pub fn serialize_tokens(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(TOKEN_CACHE_HEADER + self.tokens.len() * 9);
+        bytes.extend_from_slice(TOKEN_CACHE_MAGIC);
+        bytes.push(TOKEN_CACHE_VERSION);
+        bytes.extend_from_slice(&(self.src.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&source_hash(&self.src).to_le_bytes());
+        bytes.extend_from_slice(&(self.tokens.len() as u32).to_le_bytes());
+        for token in self.tokens.iter() {
+            bytes.extend_from_slice(&token.start.to_le_bytes());
+            bytes.extend_from_slice(&token.payload.to_le_bytes());
+            bytes.push(token.tag);
+        }
+        bytes
+    }
+
+    // None if the blob is broken, from another version or from another source
+    pub fn deserialize_tokens<'b>(src: &'b str, bytes: &[u8]) -> Option<JsonParser<'b>> {
+        use core::convert::TryInto;
+        let u32_at = |i: usize| -> Option<u32> {
+            Some(u32::from_le_bytes(bytes.get(i..i + 4)?.try_into().ok()?))
+        };
+        if bytes.get(..4)? != TOKEN_CACHE_MAGIC || bytes.get(4) != Some(&TOKEN_CACHE_VERSION) {
+            return None;
+        }
+        if u32_at(5)? as usize != src.len() || u32_at(9)? != source_hash(src) {
+            return None;
+        }
+        let count = u32_at(13)? as usize;
+        let body = &bytes[TOKEN_CACHE_HEADER..];
+        if body.len() != count * 9 {
+            return None;
+        }
+        // the tokens must be what the tokenizer makes of src: slices on char boundaries,
+        // strings in quotes, containers nested with the close index and count of their close
+        let slice = |start: usize, len: usize| src.get(start..start.checked_add(len)?);
+        let mut tokens: Vec<JsonToken> = Vec::with_capacity(count);
+        // (open index, value tokens inside). objects alternate key and value
+        let mut stack: Vec<(usize, usize)> = Vec::new();
+        let mut roots = 0;
+        for (i, chunk) in body.chunks_exact(9).enumerate() {
+            let token = JsonToken {
+                start: u32::from_le_bytes(chunk[0..4].try_into().ok()?),
+                payload: u32::from_le_bytes(chunk[4..8].try_into().ok()?),
+                tag: chunk[8],
+            };
+            let (start, payload) = (token.start(), token.payload as usize);
+            let valid = match token.tag {
+                TAG_NULL => slice(start, 4) == Some("null"),
+                TAG_TRUE => slice(start, 4) == Some("true"),
+                TAG_FALSE => slice(start, 5) == Some("false"),
+                TAG_NUMBER | TAG_INTEGER => payload > 0 && slice(start, payload).is_some(),
+                TAG_STRING => slice(start, payload)
+                    .is_some_and(|s| s.len() >= 2 && s.starts_with('"') && s.ends_with('"')),
+                TAG_ARRAY_OPEN => slice(start, 1) == Some("[") && payload > i && payload < count,
+                TAG_OBJECT_OPEN => slice(start, 1) == Some("{") && payload > i && payload < count,
+                TAG_ARRAY_CLOSE | TAG_OBJECT_CLOSE => match stack.pop() {
+                    Some((open, children)) => {
+                        let (open_tag, close, members) = match tokens[open].tag {
+                            TAG_ARRAY_OPEN => ("]", TAG_ARRAY_CLOSE, Some(children)),
+                            _ if children % 2 == 0 => ("}", TAG_OBJECT_CLOSE, Some(children / 2)),
+                            _ => ("}", TAG_OBJECT_CLOSE, None),
+                        };
+                        token.tag == close
+                            && tokens[open].payload as usize == i
+                            && members == Some(payload)
+                            && slice(start, 1) == Some(open_tag)
+                    }
+                    None => false,
+                },
+                // comma, colon and comment are never stored
+                _ => false,
+            };
+            if !valid {
+                return None;
+            }
+            if token.tag != TAG_ARRAY_CLOSE && token.tag != TAG_OBJECT_CLOSE {
+                match stack.last_mut() {
+                    Some((open, children)) => {
+                        // object keys are strings
+                        if tokens[*open].tag == TAG_OBJECT_OPEN
+                            && *children % 2 == 0
+                            && token.tag != TAG_STRING
+                        {
+                            return None;
+                        }
+                        *children += 1;
+                    }
+                    None => roots += 1,
+                }
+                if token.tag == TAG_ARRAY_OPEN || token.tag == TAG_OBJECT_OPEN {
+                    stack.push((i, 0));
+                }
+            }
+            tokens.push(token);
+        }
+        if roots != 1 || !stack.is_empty() {
+            return None;
+        }
+        Some(JsonParser {
+            src: Cow::Borrowed(src),
+            tokens: Cow::Owned(tokens),
+        })
+    }
+
+    // one token per line. "index: start data", containers also show the close index
+    pub fn dump_tokens(&self) -> String {
+        use core::fmt::Write;
+        let mut dump = String::new();
+        for (i, token) in self.tokens.iter().enumerate() {
+            write!(dump, "{}: {} {}", i, token.start(), token.data()).unwrap();
+            match token.data() {
+                JsonTokenData::Value(JsonValue::ArrayOpen(close_index))
+                | JsonTokenData::Value(JsonValue::ObjectOpen(close_index)) => {
+                    write!(dump, " -> {}", close_index).unwrap()
+                }
+                _ => (),
+            }
+            dump.push('\n');
+        }
+        dump
+    }
+
+    pub fn next_sibling_index(&self, index: usize) -> usize {
+        let token = self.tokens[index];
+        match token.data() {
+            JsonTokenData::Value(value) => match value {
+                JsonValue::ArrayOpen(close_index) => close_index + 1,
+                JsonValue::ObjectOpen(close_index) => close_index + 1,
+                _ => index + 1,
+            },
+            _ => panic!(),
+        }
+    }
+
+    // direct children of a container. array elements, or key, value, key, value... of an object.
+    // empty for other tokens
+    pub fn children(&self, container_index: usize) -> impl Iterator<Item = usize> + '_ {
+        let (mut current, close_index) = match self.tokens[container_index].data() {
+            JsonTokenData::Value(JsonValue::ArrayOpen(close_index))
+            | JsonTokenData::Value(JsonValue::ObjectOpen(close_index)) => {
+                (container_index + 1, close_index)
+            }
+            _ => (0, 0),
+        };
+        core::iter::from_fn(move || {
+            if current >= close_index {
+                return None;
+            }
+            let index = current;
+            current = self.next_sibling_index(index);
+            Some(index)
+        })
+    }
+
+    // byte length of a scalar in src, quotes included. None for containers and non-value tokens
+    pub fn value_len(&self, index: usize) -> Option<usize> {
+        match self.tokens[index].data() {
+            JsonTokenData::Value(JsonValue::Null()) => Some(4),
+            JsonTokenData::Value(JsonValue::True()) => Some(4),
+            JsonTokenData::Value(JsonValue::False()) => Some(5),
+            JsonTokenData::Value(JsonValue::Number(n, _)) => Some(n),
+            JsonTokenData::Value(JsonValue::String(n)) => Some(n),
+            _ => None,
+        }
+    }
+
+    // byte range (start, end) of the token in src
+    pub fn span(&self, index: usize) -> (usize, usize) {
+        let token = &self.tokens[index];
+        let end = match token.data() {
+            JsonTokenData::Value(value) => match value {
+                JsonValue::ArrayOpen(close_index) => {
+                    let close = self.tokens[close_index];
+                    close.start() + 1
+                }
+                JsonValue::ObjectOpen(close_index) => {
+                    let close = self.tokens[close_index];
+                    close.start() + 1
+                }
+                _ => token.start() + self.value_len(index).unwrap(),
             },
-            _ => token.start + 1,
+            _ => token.start() + 1,
+        };
+
+        (token.start(), end)
+    }
+
+    // innermost value token that contains offset
+    pub fn token_at_offset(&self, offset: usize) -> Option<usize> {
+        let contains = |index: usize| {
+            let (start, end) = self.span(index);
+            start <= offset && offset < end
         };
 
-        &self.src[token.start..end]
+        if self.tokens.is_empty() || !contains(0) {
+            return None;
+        }
+
+        let mut current = 0;
+        'descend: loop {
+            let close_index = match self.tokens[current].data() {
+                JsonTokenData::Value(JsonValue::ArrayOpen(close_index)) => close_index,
+                JsonTokenData::Value(JsonValue::ObjectOpen(close_index)) => close_index,
+                _ => return Some(current),
+            };
+
+            let mut child = current + 1;
+            while child < close_index {
+                if contains(child) {
+                    current = child;
+                    continue 'descend;
+                }
+                child = self.next_sibling_index(child);
+            }
+            return Some(current);
+        }
+    }
+
+    pub fn get_slice(&self, index: usize) -> &str {
+        let (start, end) = self.span(index);
+        &self.src[start..end]
+    }
+
+    // None for punctuation tokens
+    pub fn try_get_slice(&self, index: usize) -> Option<&str> {
+        match self.tokens[index].data() {
+            JsonTokenData::Value(_) => Some(self.get_slice(index)),
+            _ => None,
+        }
     }
 
     pub fn get_int(&self, index: usize) -> Option<i64> {
         let token = &self.tokens[index];
-        match token.data {
-            JsonTokenData::Value(JsonValue::Number(len)) => {
-                let segment = &self.src[token.start..token.start + len];
-                if let Ok(value) = segment.parse::<i64>() {
-                    Some(value)
+        match token.data() {
+            JsonTokenData::Value(JsonValue::Number(len, _)) => {
+                let segment = &self.src[token.start()..token.start() + len];
+                segment.parse::<i64>().ok()
+            }
+            _ => None,
+        }
+    }
+
+    pub fn get_int_checked(&self, index: usize) -> Result<i64, NumberError> {
+        let token = &self.tokens[index];
+        match token.data() {
+            JsonTokenData::Value(JsonValue::Number(len, _)) => {
+                let segment = &self.src[token.start()..token.start() + len];
+                segment.parse::<i64>().map_err(|error| match error.kind() {
+                    IntErrorKind::PosOverflow | IntErrorKind::NegOverflow => NumberError::Overflow,
+                    _ => NumberError::NotAnInteger,
+                })
+            }
+            _ => Err(NumberError::NotANumber),
+        }
+    }
+
+    pub fn get_f64(&self, index: usize) -> Option<f64> {
+        let token = &self.tokens[index];
+        match token.data() {
+            JsonTokenData::Value(JsonValue::Number(len, _)) => {
+                let segment = &self.src[token.start()..token.start() + len];
+                segment.parse::<f64>().ok()
+            }
+            _ => None,
+        }
+    }
+
+    // without float rounding. None if out of the Decimal range or precision
+    #[cfg(feature = "decimal")]
+    pub fn get_decimal(&self, index: usize) -> Option<rust_decimal::Decimal> {
+        use rust_decimal::Decimal;
+
+        let token = &self.tokens[index];
+        match token.data() {
+            JsonTokenData::Value(JsonValue::Number(len, _)) => {
+                let segment = &self.src[token.start()..token.start() + len];
+                if segment.contains(['e', 'E']) {
+                    Decimal::from_scientific(segment).ok()
                 } else {
-                    None
+                    Decimal::from_str_exact(segment).ok()
+                }
+            }
+            _ => None,
+        }
+    }
+
+    // Float if the number has a fraction or exponent, or does not fit in 64 bits
+    pub fn get_number(&self, index: usize) -> Option<Number> {
+        let token = &self.tokens[index];
+        match token.data() {
+            JsonTokenData::Value(JsonValue::Number(len, is_integer)) => {
+                let segment = &self.src[token.start()..token.start() + len];
+                if !is_integer {
+                    return segment.parse::<f64>().ok().map(Number::Float);
+                }
+                if let Ok(value) = segment.parse::<i64>() {
+                    return Some(Number::Int(value));
                 }
+                if let Ok(value) = segment.parse::<u64>() {
+                    return Some(Number::UInt(value));
+                }
+                segment.parse::<f64>().ok().map(Number::Float)
             }
             _ => None,
         }
     }
 
+    // element count stored in the close token. None if not an array
+    pub fn array_len(&self, index: usize) -> Option<usize> {
+        if let JsonTokenData::Value(JsonValue::ArrayOpen(close_index)) = self.tokens[index].data() {
+            if let JsonTokenData::ArrayClose(count) = self.tokens[close_index].data() {
+                return Some(count);
+            }
+        }
+        None
+    }
+
+    // member count stored in the close token. None if not an object
+    pub fn object_len(&self, index: usize) -> Option<usize> {
+        if let JsonTokenData::Value(JsonValue::ObjectOpen(close_index)) = self.tokens[index].data()
+        {
+            if let JsonTokenData::ObjectClose(count) = self.tokens[close_index].data() {
+                return Some(count);
+            }
+        }
+        None
+    }
+
     pub fn get_bool(&self, index: usize) -> Option<bool> {
         let token = &self.tokens[index];
-        match token.data {
+        match token.data() {
             JsonTokenData::Value(value) => match value {
                 JsonValue::True() => Some(true),
                 JsonValue::False() => Some(false),
@@ -572,13 +1815,914 @@ impl<'a> JsonParser<'a> {
         }
     }
 
+    // between the quotes, escapes as written
     pub fn get_string(&self, index: usize) -> Option<&str> {
+        let raw = self.get_string_raw(index)?;
+        Some(&raw[1..raw.len() - 1])
+    }
+
+    // with the quotes, as in src
+    pub fn get_string_raw(&self, index: usize) -> Option<&str> {
         let token = &self.tokens[index];
-        match token.data {
+        match token.data() {
             JsonTokenData::Value(JsonValue::String(len)) => {
-                Some(&self.src[token.start + 1..token.start + len - 1])
+                Some(&self.src[token.start()..token.start() + len])
             }
             _ => None,
         }
     }
+
+    // text of a comment token from try_process_jsonc, with the slashes
+    pub fn get_comment(&self, token: &JsonToken) -> Option<&str> {
+        match token.data() {
+            JsonTokenData::Comment(len) => self.src.get(token.start()..token.start() + len),
+            _ => None,
+        }
+    }
+
+    // get_string with NotAString instead of None
+    pub fn get_string_typed(&self, index: usize) -> Result<&str, JsonNodeError> {
+        self.get_string(index).ok_or(JsonNodeError {
+            kind: ErrorKind::NotAString,
+            index,
+        })
+    }
+
+    // get_bool with NotABoolean instead of None
+    pub fn get_bool_typed(&self, index: usize) -> Result<bool, JsonNodeError> {
+        self.get_bool(index).ok_or(JsonNodeError {
+            kind: ErrorKind::NotABoolean,
+            index,
+        })
+    }
+
+    pub fn get_null_typed(&self, index: usize) -> Result<(), JsonNodeError> {
+        match self.tokens[index].data() {
+            JsonTokenData::Value(JsonValue::Null()) => Ok(()),
+            _ => Err(JsonNodeError {
+                kind: ErrorKind::NotNull,
+                index,
+            }),
+        }
+    }
+}
+
+impl JsonParserOwned {
+    pub fn from_string(src: String) -> Result<JsonParserOwned, ParseError> {
+        let tokens = JsonParser::try_process(&src)?.tokens.into_owned();
+        Ok(JsonParserOwned {
+            parser: JsonParser {
+                src: Cow::Owned(src),
+                tokens: Cow::Owned(tokens),
+            },
+        })
+    }
+
+    pub fn parser(&self) -> &JsonParser<'_> {
+        &self.parser
+    }
+
+    // parses src into the same token buffer. returns the previous source so its
+    // buffer can be reused too. on error the previous document is kept
+    pub fn load(&mut self, src: String) -> Result<String, ParseError> {
+        let mut tokens = core::mem::take(&mut self.parser.tokens).into_owned();
+        match tokenize_into(&src, &mut tokens, ParseOptions::default()) {
+            Ok(()) => {
+                self.parser.tokens = Cow::Owned(tokens);
+                let previous = core::mem::replace(&mut self.parser.src, Cow::Owned(src));
+                Ok(previous.into_owned())
+            }
+            Err(error) => {
+                // parsed before, so this does not fail
+                tokenize_into(&self.parser.src, &mut tokens, ParseOptions::default())?;
+                self.parser.tokens = Cow::Owned(tokens);
+                Err(error)
+            }
+        }
+    }
+}
+
+#[test]
+fn try_get_slice_tests() {
+    let mut parser = JsonParser::process(r##"{"a": [1]}"##);
+    assert_eq!(Some(r##"{"a": [1]}"##), parser.try_get_slice(0));
+    assert_eq!(Some(r##""a""##), parser.try_get_slice(1));
+    assert_eq!(Some("[1]"), parser.try_get_slice(2));
+    assert_eq!(Some("1"), parser.try_get_slice(3));
+    // ArrayClose
+    assert_eq!(None, parser.try_get_slice(4));
+    // ObjectClose
+    assert_eq!(None, parser.try_get_slice(5));
+
+    parser
+        .tokens
+        .to_mut()
+        .push(JsonToken::new(3, JsonTokenData::Colon()));
+    assert_eq!(None, parser.try_get_slice(6));
+    parser
+        .tokens
+        .to_mut()
+        .push(JsonToken::new(8, JsonTokenData::Comma()));
+    assert_eq!(None, parser.try_get_slice(7));
+}
+
+#[test]
+fn get_string_tests() {
+    use super::node::JsonNode;
+
+    let parser = JsonParser::process(r##""""##);
+    assert_eq!("\"\"", parser.get_slice(0));
+    assert_eq!(Some(""), parser.get_string(0));
+
+    let parser = JsonParser::process(r##""a""##);
+    assert_eq!(Some("a"), parser.get_string(0));
+
+    // escaped quote does not close the string
+    let parser = JsonParser::process(r##""\"""##);
+    assert_eq!(r##""\"""##, parser.get_slice(0));
+    assert_eq!(Some(r##"\""##), parser.get_string(0));
+
+    let parser = JsonParser::process(r##"["\"", ""]"##);
+    assert_eq!(Some(2), JsonNode::new(&parser).array_len());
+    assert_eq!(Some(""), parser.get_string(2));
+}
+
+#[test]
+fn span_tests() {
+    let parser = JsonParser::process(r##" {"a": [1, 2], "b": "xy"}"##);
+    // object
+    assert_eq!((1, 25), parser.span(0));
+    // array
+    assert_eq!((7, 13), parser.span(2));
+    // scalar
+    assert_eq!((8, 9), parser.span(3));
+    assert_eq!((20, 24), parser.span(7));
+    assert_eq!(r##""xy""##, &parser.src[20..24]);
+}
+
+#[test]
+fn token_at_offset_tests() {
+    let parser = JsonParser::process(r##"{"a": [1, {"b": true}]}"##);
+    // '{' of root
+    assert_eq!(Some(0), parser.token_at_offset(0));
+    // "a"
+    assert_eq!(Some(1), parser.token_at_offset(2));
+    // 1
+    assert_eq!(Some(3), parser.token_at_offset(7));
+    // ',' between elements belongs to the array
+    assert_eq!(Some(2), parser.token_at_offset(8));
+    // true
+    assert_eq!(Some(6), parser.token_at_offset(17));
+    assert_eq!("true", parser.get_slice(6));
+    // out of range
+    assert_eq!(None, parser.token_at_offset(100));
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn from_reader_tests() {
+    use super::node::JsonNode;
+
+    let cursor = std::io::Cursor::new(br##"{"a": [1, 2]}"##.to_vec());
+    let owned = JsonParser::from_reader(cursor).unwrap();
+    let root = JsonNode::new(owned.parser());
+    assert_eq!(Some(2), root.key("a").unwrap().array_len());
+
+    let cursor = std::io::Cursor::new(b"[1, 2".to_vec());
+    assert!(matches!(
+        JsonParser::from_reader(cursor),
+        Err(ParseError::Eof())
+    ));
+
+    let cursor = std::io::Cursor::new(vec![b'"', 0xff, b'"']);
+    assert!(matches!(
+        JsonParser::from_reader(cursor),
+        Err(ParseError::Io(std::io::ErrorKind::InvalidData))
+    ));
+}
+
+#[test]
+fn get_int_checked_tests() {
+    let parser = JsonParser::process(
+        r##"[42, -9223372036854775808, 9223372036854775808, -9223372036854775809, 1.5, "1"]"##,
+    );
+    assert_eq!(Ok(42), parser.get_int_checked(1));
+    assert_eq!(Ok(i64::MIN), parser.get_int_checked(2));
+    assert_eq!(Err(NumberError::Overflow), parser.get_int_checked(3));
+    assert_eq!(Err(NumberError::Overflow), parser.get_int_checked(4));
+    assert_eq!(Err(NumberError::NotAnInteger), parser.get_int_checked(5));
+    assert_eq!(Err(NumberError::NotANumber), parser.get_int_checked(6));
+    assert_eq!(Err(NumberError::NotANumber), parser.get_int_checked(0));
+}
+
+#[test]
+fn dump_tokens_tests() {
+    let parser = JsonParser::process("[1,2]");
+    assert_eq!(
+        "0: 0 [ -> 3\n1: 1 number[1]\n2: 3 number[1]\n3: 4 ]\n",
+        parser.dump_tokens()
+    );
+}
+
+#[test]
+fn mismatched_close_tests() {
+    match JsonParser::try_process("[1}") {
+        Err(ParseError::MismatchedClose {
+            open_offset: 0,
+            open_char: '[',
+            close_offset: 2,
+            close_char: '}',
+        }) => (),
+        _ => panic!(),
+    }
+
+    let e = JsonParser::try_process(r##"{"a":1]"##).err().unwrap();
+    assert!(matches!(
+        e,
+        ParseError::MismatchedClose {
+            open_offset: 0,
+            open_char: '{',
+            close_offset: 6,
+            close_char: ']',
+        }
+    ));
+    assert_eq!("`{` at 0 closed by `]` at 6", format!("{}", e));
+
+    // nested. the innermost open is reported
+    assert!(matches!(
+        JsonParser::try_process(r##"{"a": [1, 2}}"##),
+        Err(ParseError::MismatchedClose {
+            open_offset: 6,
+            open_char: '[',
+            ..
+        })
+    ));
+}
+
+#[test]
+fn process_recovering_tests() {
+    use super::node::JsonNode;
+
+    let (parser, errors) = JsonParser::process_recovering("[1, x, 3, 4 5]");
+    assert_eq!(2, errors.len());
+    assert!(matches!(errors[0], ParseError::Unknown(4, 'x')));
+    assert!(matches!(
+        errors[1],
+        ParseError::Value(12, JsonValue::Number(1, true), _)
+    ));
+    let parser = parser.unwrap();
+    assert_eq!(Some(4), JsonNode::new(&parser).array_len());
+    assert_eq!("3", parser.get_slice(3));
+
+    // stops at eof
+    let (parser, errors) = JsonParser::process_recovering(r##"{"a": tru, "b": [1,], "c": ["##);
+    assert!(parser.is_none());
+    assert_eq!(3, errors.len());
+    assert!(matches!(errors[0], ParseError::Unknown(9, ',')));
+    assert!(matches!(errors[1], ParseError::Unknown(19, ']')));
+    assert!(matches!(errors[2], ParseError::Eof()));
+
+    let (parser, errors) = JsonParser::process_recovering("[1]");
+    assert!(parser.is_some());
+    assert!(errors.is_empty());
+}
+
+#[cfg(feature = "bumpalo")]
+#[test]
+fn try_process_in_tests() {
+    use super::node::JsonNode;
+
+    let docs: Vec<String> = (0..1000)
+        .map(|i| {
+            format!(
+                r##"{{"index": {}, "items": [{}, {}, {}]}}"##,
+                i,
+                i,
+                i + 1,
+                i + 2
+            )
+        })
+        .collect();
+
+    let mut bump = bumpalo::Bump::new();
+    let mut sum = 0;
+    for chunk in docs.chunks(100) {
+        bump.reset();
+        for doc in chunk {
+            let parser = JsonParser::try_process_in(doc, &bump).unwrap();
+            let node = JsonNode::new(&parser);
+            assert!(matches!(parser.tokens, Cow::Borrowed(_)));
+            sum += node.key("index").unwrap().get_int().unwrap();
+            assert_eq!(Some(3), node.key("items").unwrap().array_len());
+        }
+    }
+    assert_eq!((0..1000).sum::<i64>(), sum);
+
+    assert!(JsonParser::try_process_in("[1, 2", &bump).is_err());
+}
+
+#[test]
+fn whitespace_tests() {
+    let parser = JsonParser::process(" \t\r\n[ 1 ,\n\t2 ]\r\n");
+    assert_eq!(4, parser.tokens.len());
+    assert_eq!("2", parser.get_slice(2));
+
+    // no-break space
+    assert!(matches!(
+        JsonParser::try_process("[1,\u{a0}2]"),
+        Err(ParseError::Unknown(3, '\u{a0}'))
+    ));
+    assert!(JsonParser::try_process("\u{3000}1").is_err());
+    assert!(super::events::parse_events("[1,\u{a0}2]", &mut |_| {}).is_err());
+}
+
+#[test]
+fn packed_token_tests() {
+    use core::mem::size_of;
+
+    // tokens of a large document take less than half of (usize, JsonTokenData)
+    assert_eq!(12, size_of::<JsonToken>());
+    assert!(size_of::<JsonToken>() * 2 <= size_of::<(usize, JsonTokenData)>());
+
+    for data in [
+        JsonTokenData::Value(JsonValue::Null()),
+        JsonTokenData::Value(JsonValue::True()),
+        JsonTokenData::Value(JsonValue::False()),
+        JsonTokenData::Value(JsonValue::Number(3, false)),
+        JsonTokenData::Value(JsonValue::Number(3, true)),
+        JsonTokenData::Value(JsonValue::String(5)),
+        JsonTokenData::Value(JsonValue::ArrayOpen(7)),
+        JsonTokenData::Value(JsonValue::ObjectOpen(11)),
+        JsonTokenData::Comma(),
+        JsonTokenData::Colon(),
+        JsonTokenData::ArrayClose(13),
+        JsonTokenData::ObjectClose(17),
+    ] {
+        let token = JsonToken::new(19, data);
+        assert_eq!(19, token.start());
+        assert_eq!(data, token.data());
+    }
+
+    let parser = JsonParser::process(
+        r##"{"a": [1, -2.5e3, "x"], "b": {"c": null, "d": true}, "e": false}"##,
+    );
+    assert_eq!(
+        r##"0: 0 { -> 16
+1: 1 string[3]
+2: 6 [ -> 6
+3: 7 number[1]
+4: 10 number[6]
+5: 18 string[3]
+6: 21 ]
+7: 24 string[3]
+8: 29 { -> 13
+9: 30 string[3]
+10: 35 null
+11: 41 string[3]
+12: 46 true
+13: 50 }
+14: 53 string[3]
+15: 58 false
+16: 63 }
+"##,
+        parser.dump_tokens()
+    );
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn process_array_parallel_tests() {
+    let src = format!(
+        "[\n{}\n]",
+        (0..1000)
+            .map(|i| format!(
+                r##"{{"id": {}, "name": "n\"{}", "values": [{}, {}.5, [true, null]], "empty": {{}}}}"##,
+                i, i, i, i
+            ))
+            .collect::<Vec<String>>()
+            .join(",\n")
+    );
+    let sequential = JsonParser::process(&src);
+    let parallel = JsonParser::process_array_parallel(&src);
+    assert_eq!(sequential.tokens, parallel.tokens);
+
+    for src in [" [ ] ", "[1]", r##"[",]", "[", {"a": "]"}]"##, "1", "{}"] {
+        assert_eq!(
+            JsonParser::process(src).tokens,
+            JsonParser::process_array_parallel(src).tokens
+        );
+    }
+
+    // errors are the same as sequential
+    for src in ["[1 2]", "[1,,2]", "[1, }", "[", "[1, [2]"] {
+        assert_eq!(
+            JsonParser::try_process(src).err().map(|e| format!("{}", e)),
+            JsonParser::try_process_array_parallel(src)
+                .err()
+                .map(|e| format!("{}", e))
+        );
+    }
+}
+
+#[test]
+fn process_strict_tests() {
+    let strict = |src: &str| {
+        JsonParser::try_process_strict(src)
+            .map(|_| ())
+            .map_err(|e| format!("{}", e))
+    };
+
+    assert!(strict(r##" {"a": [0, -1, 1.5, 2e10, 3E-2, -0.0e+1], "b": "\\n\\u00e9"} "##).is_ok());
+
+    // leading zero
+    assert!(JsonParser::try_process("01").is_ok());
+    assert_eq!(Err("'1' at 1 is unknown".to_string()), strict("01"));
+    assert_eq!(Err("'0' at 3 is unknown".to_string()), strict("[-00]"));
+    // missing digits
+    assert!(strict("[1.]").is_err());
+    assert!(strict("[-]").is_err());
+    assert!(strict("[1e+]").is_err());
+    // trailing content
+    assert_eq!(Err("trailing content at 2".to_string()), strict("1 2"));
+    assert_eq!(Err("trailing content at 2".to_string()), strict("{}junk"));
+    assert!(strict("[1]\r\n").is_ok());
+    // control char and unknown escape in a string
+    assert!(strict("\"a\tb\"").is_err());
+    assert_eq!(Err("'x' at 3 is unknown".to_string()), strict(r##""a\x""##));
+    assert!(strict(r##""\u12x4""##).is_err());
+}
+
+#[test]
+fn trailing_content_tests() {
+    assert!(matches!(
+        JsonParser::try_process("{} x"),
+        Err(ParseError::TrailingContent(3))
+    ));
+    assert!(matches!(
+        JsonParser::try_process("[1,2] 3"),
+        Err(ParseError::TrailingContent(6))
+    ));
+    assert!(JsonParser::try_process("[1,2] \r\n\t ").is_ok());
+
+    let (parser, errors) = JsonParser::process_recovering("[1] [2]");
+    assert!(matches!(errors[..], [ParseError::TrailingContent(4)]));
+    assert_eq!(Some("[1]"), parser.unwrap().try_get_slice(0));
+}
+
+#[test]
+fn line_col_tests() {
+    let lf = "{\n  \"a\": 1,\n  \"b\": x\n}";
+    let crlf = lf.replace('\n', "\r\n");
+    let mixed = "{\r\n  \"a\": 1,\n  \"b\": x\r\n}";
+    for src in [lf, crlf.as_str(), mixed] {
+        let offset = src.find('x').unwrap();
+        assert_eq!((3, 8), line_col(src, offset));
+        let error = JsonParser::try_process(src).err().unwrap();
+        assert_eq!(
+            "3:8: 'x' at OFFSET is unknown\n  \"b\": x\n       ^\n"
+                .replace("OFFSET", &format!("{}", offset)),
+            error.render(src)
+        );
+    }
+    assert_eq!((1, 1), line_col("", 0));
+    assert_eq!((2, 1), line_col("\r", 1));
+    assert_eq!((1, 3), line_col("\u{e9}ab", 3));
+}
+
+#[test]
+fn process_utf16_tests() {
+    let src = "{\"a\": [1, \"\u{e9}\u{1F600}\"]}";
+    let utf8 = JsonParser::process(src);
+    let le: Vec<u8> = src.encode_utf16().flat_map(u16::to_le_bytes).collect();
+    let owned = JsonParser::process_utf16(&le, Utf16Endian::Little);
+    assert_eq!(utf8.tokens, owned.parser().tokens);
+    assert_eq!(src, owned.parser().src);
+
+    // the BOM wins over the argument
+    let mut be = vec![0xFE, 0xFF];
+    be.extend(src.encode_utf16().flat_map(u16::to_be_bytes));
+    let owned = JsonParser::process_utf16(&be, Utf16Endian::Little);
+    assert_eq!(utf8.tokens, owned.parser().tokens);
+
+    assert!(matches!(
+        JsonParser::try_process_utf16(&le[..3], Utf16Endian::Little),
+        Err(ParseError::InvalidUtf16(2))
+    ));
+    // unpaired high surrogate
+    let lone: Vec<u8> = [0x22, 0xD83D, 0x22]
+        .iter()
+        .flat_map(|u: &u16| u.to_le_bytes())
+        .collect();
+    assert!(matches!(
+        JsonParser::try_process_utf16(&lone, Utf16Endian::Little),
+        Err(ParseError::InvalidUtf16(2))
+    ));
+}
+
+#[test]
+fn serialize_tokens_tests() {
+    let src = r##"{"a": [1, 2.5, "x"], "b": {"c": null, "d": true}}"##;
+    let parser = JsonParser::process(src);
+    let bytes = parser.serialize_tokens();
+    let cached = JsonParser::deserialize_tokens(src, &bytes).unwrap();
+    assert_eq!(parser.tokens, cached.tokens);
+    assert_eq!(Some(2.5), cached.get_f64(4));
+
+    // another version
+    let mut stale = bytes.clone();
+    stale[4] += 1;
+    assert!(JsonParser::deserialize_tokens(src, &stale).is_none());
+    // another source
+    let other = src.replace("true", "null");
+    assert!(JsonParser::deserialize_tokens(&other, &bytes).is_none());
+    // truncated
+    assert!(JsonParser::deserialize_tokens(src, &bytes[..bytes.len() - 1]).is_none());
+    assert!(JsonParser::deserialize_tokens(src, &bytes[..3]).is_none());
+    assert!(JsonParser::deserialize_tokens(src, b"JPTK").is_none());
+    assert!(JsonParser::deserialize_tokens(src, &bytes[..TOKEN_CACHE_HEADER - 1]).is_none());
+
+    // {"a": [1]} with the object open pointing at the array close
+    let src = r##"{"a": [1]}"##;
+    let mut corrupt = JsonParser::process(src).serialize_tokens();
+    assert!(JsonParser::deserialize_tokens(src, &corrupt).is_some());
+    corrupt[TOKEN_CACHE_HEADER + 4..TOKEN_CACHE_HEADER + 8].copy_from_slice(&4u32.to_le_bytes());
+    assert!(JsonParser::deserialize_tokens(src, &corrupt).is_none());
+
+    // accepted with the payload of token i replaced
+    let accepts = |src: &str, i: usize, payload: u32| {
+        let mut bytes = JsonParser::process(src).serialize_tokens();
+        let at = TOKEN_CACHE_HEADER + i * 9 + 4;
+        bytes[at..at + 4].copy_from_slice(&payload.to_le_bytes());
+        JsonParser::deserialize_tokens(src, &bytes).is_some()
+    };
+    // string shorter than its quotes, or ending inside a char
+    assert!(accepts(r##"["ab"]"##, 1, 4));
+    assert!(!accepts(r##"["ab"]"##, 1, 1));
+    assert!(!accepts(r##"["é"]"##, 1, 3));
+    assert!(!accepts(r##"["é"]"##, 1, 5));
+    // [[1], 2]: the inner open pointing at the outer close
+    assert!(!accepts("[[1], 2]", 1, 5));
+    // wrong count in a close
+    assert!(!accepts("[[1], 2]", 3, 2));
+    assert!(!accepts(r##"{"a": 1}"##, 3, 2));
+    // number of zero length
+    assert!(!accepts("[1]", 1, 0));
+}
+
+#[test]
+fn value_error_expected_tests() {
+    let message = |src: &str| JsonParser::try_process(src).err().unwrap().to_string();
+    assert_eq!(
+        "number[1] at 3 is invalid (expected ',' or ']')",
+        message("[1 2]")
+    );
+    assert_eq!(
+        "string[3] at 8 is invalid (expected ',' or '}')",
+        message(r##"{"a": 1 "b": 2}"##)
+    );
+    assert_eq!(
+        "true at 5 is invalid (expected ':')",
+        message(r##"{"a" true}"##)
+    );
+}
+
+#[test]
+fn number_len_tests() {
+    let number_len = |parser: &JsonParser, index: usize| match parser.tokens[index].data() {
+        JsonTokenData::Value(JsonValue::Number(len, _)) => len,
+        data => panic!("{:?}", data),
+    };
+
+    // single digit at offset 0
+    let parser = JsonParser::process("5");
+    assert_eq!(1, number_len(&parser, 0));
+    assert_eq!(Some(5), parser.get_int(0));
+
+    let long = "9".repeat(300);
+    let parser = JsonParser::process(&long);
+    assert_eq!(300, number_len(&parser, 0));
+    assert_eq!(long, parser.get_slice(0));
+    assert_eq!(None, parser.get_int(0));
+    assert_eq!(Some(1e300), parser.get_f64(0));
+
+    // last thing in the input, no trailing whitespace
+    let parser = JsonParser::process("[1, 23]");
+    assert_eq!(2, number_len(&parser, 2));
+    let parser = JsonParser::process("  -42");
+    assert_eq!(3, number_len(&parser, 0));
+    assert_eq!("-42", parser.get_slice(0));
+}
+
+#[test]
+fn number_at_eof_tests() {
+    // the digit loops end on peek() == None
+    let parser = JsonParser::process("123");
+    assert_eq!("123", parser.get_slice(0));
+    assert_eq!(Some(123), parser.get_int(0));
+    for (src, expected) in [
+        ("1.5", 1.5),
+        ("1e2", 100.0),
+        ("-2.5E+1", -25.0),
+        ("0.125", 0.125),
+    ] {
+        let parser = JsonParser::process(src);
+        assert_eq!(src, parser.get_slice(0));
+        assert_eq!(Some(expected), parser.get_f64(0));
+    }
+    assert!(matches!(
+        JsonParser::try_process("1e"),
+        Err(ParseError::Eof())
+    ));
+}
+
+#[test]
+fn process_with_progress_tests() {
+    let src = format!("[{}]", vec!["[1, \"x\"]"; 2000].join(", "));
+    let mut offsets = Vec::new();
+    let parser = JsonParser::process_with_progress(&src, |done, total| {
+        assert_eq!(src.len(), total);
+        offsets.push(done);
+    });
+    assert_eq!(JsonParser::process(&src).tokens, parser.tokens);
+    // a token every 1024 plus the last call
+    assert_eq!(parser.tokens.len() / PROGRESS_INTERVAL + 1, offsets.len());
+    assert!(offsets.windows(2).all(|w| w[0] < w[1]));
+    assert_eq!(Some(&src.len()), offsets.last());
+
+    let mut calls = 0;
+    assert!(JsonParser::try_process_with_progress("[1,", |_, _| calls += 1).is_err());
+    assert_eq!(0, calls);
+}
+
+#[test]
+fn children_tests() {
+    let parser = JsonParser::process(r##"[1, [2, 3], {"a": 4}, "x"]"##);
+    assert_eq!(vec![1, 2, 6, 10], parser.children(0).collect::<Vec<_>>());
+    assert_eq!(vec![3, 4], parser.children(2).collect::<Vec<_>>());
+    assert_eq!(vec![7, 8], parser.children(6).collect::<Vec<_>>());
+    assert_eq!(0, parser.children(1).count());
+
+    let parser = JsonParser::process(r##"{"a": {"b": []}, "c": null}"##);
+    let children: Vec<&str> = parser.children(0).map(|i| parser.get_slice(i)).collect();
+    assert_eq!(
+        vec![r##""a""##, r##"{"b": []}"##, r##""c""##, "null"],
+        children
+    );
+    assert_eq!(0, JsonParser::process("{}").children(0).count());
+}
+
+#[cfg(feature = "decimal")]
+#[test]
+fn get_decimal_tests() {
+    use rust_decimal::Decimal;
+
+    let parser = JsonParser::process(
+        "[0.1, 79228162514264337593543950335, 3.1415926535897932384626433832, 1.5e3, -2E-2, 1e40, \"1\"]",
+    );
+    let decimal = |index| parser.get_decimal(index).map(|d: Decimal| d.to_string());
+    assert_eq!(Some("0.1".to_string()), decimal(1));
+    assert_eq!(
+        Some("79228162514264337593543950335".to_string()),
+        decimal(2)
+    );
+    assert_eq!(
+        Some("3.1415926535897932384626433832".to_string()),
+        decimal(3)
+    );
+    assert_eq!(Some("1500".to_string()), decimal(4));
+    assert_eq!(Some("-0.02".to_string()), decimal(5));
+    assert_eq!(None, decimal(6));
+    assert_eq!(None, decimal(7));
+}
+
+#[test]
+fn owned_load_tests() {
+    use super::node::JsonNode;
+
+    let capacity = |owned: &JsonParserOwned| match &owned.parser.tokens {
+        Cow::Owned(tokens) => tokens.capacity(),
+        Cow::Borrowed(_) => 0,
+    };
+
+    let mut owned = JsonParserOwned::from_string(String::from("[1, 2, 3, [4, 5]]")).unwrap();
+    let large = capacity(&owned);
+    assert!(large >= 7);
+
+    let previous = owned.load(String::from(r##"{"a": true}"##)).unwrap();
+    assert_eq!("[1, 2, 3, [4, 5]]", previous);
+    assert_eq!(
+        Some(true),
+        JsonNode::new(owned.parser()).key("a").unwrap().get_bool()
+    );
+    assert_eq!(large, capacity(&owned));
+
+    // reuse the returned buffer for the next document
+    let mut src = previous;
+    src.clear();
+    src.push_str("\"x\"");
+    owned.load(src).unwrap();
+    assert_eq!(Some("x"), owned.parser().get_string(0));
+    assert_eq!(1, owned.parser().tokens.len());
+    assert_eq!(large, capacity(&owned));
+
+    // the previous document is kept on error
+    assert!(owned.load(String::from("[1,")).is_err());
+    assert_eq!("\"x\"", owned.parser().src);
+    assert_eq!(Some("x"), owned.parser().get_string(0));
+}
+
+#[test]
+fn get_string_typed_tests() {
+    let parser = JsonParser::process(r##"["a", 1]"##);
+    assert_eq!(Ok("a"), parser.get_string_typed(1));
+    let error = parser.get_string_typed(2).err().unwrap();
+    assert_eq!(ErrorKind::NotAString, error.kind);
+    assert_eq!(2, error.index);
+    assert_eq!("node 2: not a string", error.to_string());
+}
+
+#[test]
+fn get_bool_null_typed_tests() {
+    let parser = JsonParser::process(r##"[true, false, null, "true", 0]"##);
+    assert_eq!(Ok(true), parser.get_bool_typed(1));
+    assert_eq!(Ok(false), parser.get_bool_typed(2));
+    for index in [3, 4, 5] {
+        let error = parser.get_bool_typed(index).err().unwrap();
+        assert_eq!(ErrorKind::NotABoolean, error.kind);
+        assert_eq!(index, error.index);
+    }
+    assert_eq!(
+        "node 4: not a boolean",
+        parser.get_bool_typed(4).err().unwrap().to_string()
+    );
+
+    assert_eq!(Ok(()), parser.get_null_typed(3));
+    for index in [0, 2, 4, 5] {
+        let error = parser.get_null_typed(index).err().unwrap();
+        assert_eq!(ErrorKind::NotNull, error.kind);
+        assert_eq!(index, error.index);
+    }
+    assert_eq!(
+        "node 2: not null",
+        parser.get_null_typed(2).err().unwrap().to_string()
+    );
+}
+
+#[test]
+fn process_jsonc_tests() {
+    use super::node::JsonNode;
+
+    let src = "// settings\r\n{\n  \"a\": \"http://x\", /* b */ \"b\": [1, /* c */ 2]\n} // end";
+    let (parser, comments) = JsonParser::try_process_jsonc(src).unwrap();
+    let texts: Vec<&str> = comments
+        .iter()
+        .map(|c| parser.get_comment(c).unwrap())
+        .collect();
+    assert_eq!(vec!["// settings", "/* b */", "/* c */", "// end"], texts);
+    // same tokens as without comments
+    let plain = r##"{"a": "http://x", "b": [1, 2]}"##;
+    let expected = JsonParser::process(plain);
+    let dump = |p: &JsonParser| {
+        (0..p.tokens.len())
+            .map(|i| format!("{:?}", p.tokens[i].data()))
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(dump(&expected), dump(&parser));
+    assert_eq!(
+        Some("http://x"),
+        JsonNode::new(&parser).key("a").unwrap().get_string()
+    );
+
+    assert!(matches!(
+        JsonParser::try_process_jsonc("1 /* x"),
+        Err(ParseError::Eof())
+    ));
+    assert!(matches!(
+        JsonParser::try_process_jsonc("1 / 2"),
+        Err(ParseError::Unknown(3, ' '))
+    ));
+    // comments are not json
+    assert!(JsonParser::try_process("// x\n1").is_err());
+}
+
+#[test]
+fn max_string_len_tests() {
+    let options = ParseOptions {
+        max_string_len: Some(3),
+        ..Default::default()
+    };
+    let parser = JsonParser::try_process_with(r##"["abc", {"key": "\\n"}]"##, options).unwrap();
+    assert_eq!(Some("abc"), parser.get_string(1));
+    assert!(matches!(
+        JsonParser::try_process_with(r##"["abc", "abcd"]"##, options),
+        Err(ParseError::StringTooLong(8))
+    ));
+    // keys too
+    assert!(matches!(
+        JsonParser::try_process_with(r##"{"long": 1}"##, options),
+        Err(ParseError::StringTooLong(1))
+    ));
+    assert!(JsonParser::try_process(&format!("\"{}\"", "x".repeat(10000))).is_ok());
+}
+
+#[test]
+fn max_elements_tests() {
+    let options = ParseOptions {
+        max_elements: Some(4),
+        ..Default::default()
+    };
+    // the array itself counts
+    assert!(JsonParser::try_process_with("[1, 2, 3]", options).is_ok());
+    assert!(matches!(
+        JsonParser::try_process_with("[1, 2, 3, 4]", options),
+        Err(ParseError::TooManyElements(10))
+    ));
+    // key and value
+    assert!(JsonParser::try_process_with(r##"{"a": 1}"##, options).is_ok());
+    assert!(matches!(
+        JsonParser::try_process_with(r##"{"a": 1, "b": 2}"##, options),
+        Err(ParseError::TooManyElements(14))
+    ));
+    let wide = format!("[{}0]", "0,".repeat(100000));
+    assert!(matches!(
+        JsonParser::try_process_with(&wide, options),
+        Err(ParseError::TooManyElements(_))
+    ));
+}
+
+#[test]
+fn value_len_tests() {
+    let parser = JsonParser::process(r##"[null, false, true, -1.5e3, "a\"b", {}]"##);
+    assert_eq!(None, parser.value_len(0));
+    assert_eq!(Some(4), parser.value_len(1));
+    assert_eq!(Some(5), parser.value_len(2));
+    assert_eq!(Some(4), parser.value_len(3));
+    assert_eq!(Some(6), parser.value_len(4));
+    assert_eq!(Some(6), parser.value_len(5));
+    assert_eq!(None, parser.value_len(6));
+}
+
+#[test]
+fn strict_escapes_tests() {
+    let options = ParseOptions {
+        strict_escapes: true,
+        ..Default::default()
+    };
+    assert!(JsonParser::try_process_with(r##"["\n\u00e9\ud83d\ude00"]"##, options).is_ok());
+    // lenient by default
+    assert!(JsonParser::try_process(r##""\x""##).is_ok());
+    let error = JsonParser::try_process_with(r##""\x""##, options)
+        .err()
+        .unwrap();
+    assert!(matches!(error, ParseError::InvalidEscape(1, 'x')));
+    assert_eq!("invalid escape \\x at 1", error.to_string());
+    assert!(matches!(
+        JsonParser::try_process_with(r##"["a\u12"]"##, options),
+        Err(ParseError::InvalidEscape(3, 'u'))
+    ));
+    assert!(matches!(
+        JsonParser::try_process_with(r##"{"k": "\ud83d"}"##, options),
+        Err(ParseError::InvalidEscape(7, 'u'))
+    ));
+}
+
+#[test]
+fn element_ranges_tests() {
+    let src = " [ {\"a\": [1, 2]}, \"x,]\\\"\" ,\n        3.5 ] ";
+    let ranges = JsonParser::element_ranges(src).unwrap();
+    let elements: Vec<&str> = ranges
+        .iter()
+        .map(|&(start, end)| &src[start..end])
+        .collect();
+    assert_eq!(vec![r##"{"a": [1, 2]}"##, "\"x,]\\\"\"", "3.5"], elements);
+    let parser = JsonParser::process(&src[ranges[0].0..ranges[0].1]);
+    assert_eq!(Some(2), parser.get_int(4));
+
+    assert_eq!(Some(Vec::new()), JsonParser::element_ranges("[ ]"));
+    assert_eq!(None, JsonParser::element_ranges(r##"{"a": 1}"##));
+}
+
+#[test]
+fn is_valid_tests() {
+    for src in [
+        "0",
+        " null ",
+        "[]",
+        r##"{"a": [1, -2.5e3, "x"]}"##,
+        "\"\\u00e9\"",
+    ] {
+        assert!(super::is_valid(src), "{}", src);
+    }
+    for src in [
+        "",
+        "[1,",
+        "[1 2]",
+        r##"{"a" 1}"##,
+        "{} {}",
+        "[1]]",
+        "tru",
+        "\"abc",
+    ] {
+        assert!(!super::is_valid(src), "{}", src);
+    }
 }
@@ -0,0 +1,731 @@
+use super::super::string::escape_string;
+use super::parser::JsonSchema;
+
+// "glTFProperty.schema.json" => "glTFProperty", "accessor.sparse.schema.json" => "accessor.sparse"
+fn ref_name(reference: &str) -> &str {
+    let file = reference.rsplit('/').next().unwrap_or("");
+    file.trim_end_matches(".json").trim_end_matches(".schema")
+}
+
+// "Mesh Primitive" => "MeshPrimitive", "3D texture" => "_3DTexture".
+// anything but ascii alphanumerics separates words
+fn pascal_case(src: &str) -> String {
+    let mut name = String::new();
+    for word in src.split(|c: char| !c.is_ascii_alphanumeric()) {
+        let mut chars = word.chars();
+        if let Some(first) = chars.next() {
+            name.push(first.to_ascii_uppercase());
+            name.push_str(chars.as_str());
+        }
+    }
+    if name.is_empty() {
+        name.push_str("Root");
+    } else if name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    name
+}
+
+fn type_name(schema: &JsonSchema, fallback: &str) -> String {
+    if let Some(title) = schema.title.as_deref().filter(|title| !title.is_empty()) {
+        pascal_case(title)
+    } else if let Some(reference) = &schema.reference {
+        pascal_case(ref_name(reference))
+    } else {
+        pascal_case(fallback)
+    }
+}
+
+// generated types in output order, with the schema each name was generated for
+struct Types<'a> {
+    types: Vec<(String, &'a JsonSchema, String)>,
+}
+
+impl<'a> Types<'a> {
+    fn new() -> Self {
+        Types { types: Vec::new() }
+    }
+
+    fn contains(&self, schema: &JsonSchema) -> bool {
+        self.types.iter().any(|(_, s, _)| *s == schema)
+    }
+
+    // (name, None) if the schema already has the name or a numbered one.
+    // otherwise a new empty entry, numbered if a different schema has the name.
+    // the name is reserved before the source, so recursion finds it
+    fn reserve(&mut self, name: &str, schema: &'a JsonSchema) -> (String, Option<usize>) {
+        let mut unique = name.to_string();
+        for n in 2.. {
+            match self.types.iter().find(|(k, _, _)| *k == unique) {
+                Some((_, s, _)) if *s == schema => return (unique, None),
+                Some(_) => unique = format!("{}{}", name, n),
+                None => break,
+            }
+        }
+        self.types.push((unique.clone(), schema, String::new()));
+        (unique, Some(self.types.len() - 1))
+    }
+
+    fn set(&mut self, index: usize, src: String) {
+        self.types[index].2 = src;
+    }
+
+    fn join(&self) -> String {
+        self.types
+            .iter()
+            .map(|(_, _, src)| src.as_str())
+            .collect::<Vec<&str>>()
+            .join("\n")
+    }
+}
+
+// extras in the schema or in the base it extends, which glTF objects get from glTFProperty.
+// allOf branches other than the base are not emitted, so they do not count
+fn declares_extras(schema: &JsonSchema) -> bool {
+    schema.property("extras").is_some() || schema.ref_schema.as_deref().is_some_and(declares_extras)
+}
+
+// "x-id" => "\"x-id\"". a json string literal is also a ts string literal
+fn property_key(key: &str) -> String {
+    let mut chars = key.chars();
+    let identifier = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if identifier {
+        key.to_string()
+    } else {
+        escape_string(key)
+    }
+}
+
+// "*/" in a description would close the comment
+fn doc_comment(description: &str) -> String {
+    description.replace("*/", "*\\/")
+}
+
+struct TypeScriptGenerator<'a> {
+    types: Types<'a>,
+}
+
+impl<'a> TypeScriptGenerator<'a> {
+    fn field_type(&mut self, schema: &'a JsonSchema, fallback: &str) -> String {
+        match schema.json_type.as_deref().unwrap_or("") {
+            "string" => "string".to_string(),
+            "integer" | "number" => "number".to_string(),
+            "boolean" => "boolean".to_string(),
+            "array" => match &schema.items {
+                Some(items) => format!("{}[]", self.field_type(items, fallback)),
+                None => "any[]".to_string(),
+            },
+            "object" if schema.properties.is_empty() && schema.reference.is_none() => {
+                "{ [key: string]: any }".to_string()
+            }
+            "object" if !schema.properties.is_empty() => self.interface(schema, fallback),
+            // { "allOf": [ { "$ref": ... } ] } without own properties is the referenced type
+            _ => match &schema.ref_schema {
+                Some(ref_schema) => self.field_type(ref_schema, &type_name(schema, fallback)),
+                None if schema.reference.is_some() => type_name(schema, fallback),
+                None => "any".to_string(),
+            },
+        }
+    }
+
+    fn interface(&mut self, schema: &'a JsonSchema, fallback: &str) -> String {
+        // the reference names the base, not this interface
+        let name = match schema.title.as_deref().filter(|title| !title.is_empty()) {
+            Some(title) => pascal_case(title),
+            None => pascal_case(fallback),
+        };
+        let (name, index) = self.types.reserve(&name, schema);
+        let index = match index {
+            Some(index) => index,
+            None => return name,
+        };
+
+        let mut src = String::new();
+        if let Some(description) = &schema.description {
+            src.push_str(&format!("/** {} */\n", doc_comment(description)));
+        }
+        src.push_str(&format!("interface {}", name));
+        if let Some(reference) = &schema.reference {
+            let base = match &schema.ref_schema {
+                Some(ref_schema) => self.interface(ref_schema, ref_name(reference)),
+                None => pascal_case(ref_name(reference)),
+            };
+            src.push_str(&format!(" extends {}", base));
+        }
+        src.push_str(" {\n");
+        for (key, property) in &schema.properties {
+            if let Some(description) = &property.description {
+                src.push_str(&format!("    /** {} */\n", doc_comment(description)));
+            }
+            let optional = if schema.required.contains(key) {
+                ""
+            } else {
+                "?"
+            };
+            // glTF extras is application data of any shape
+            let field_type = if key == "extras" {
+                "any".to_string()
+            } else {
+                self.field_type(property, &format!("{}_{}", name, key))
+            };
+            // writeOnly has no typescript counterpart
+            let readonly = if property.read_only { "readonly " } else { "" };
+            src.push_str(&format!(
+                "    {}{}{}: {};\n",
+                readonly,
+                property_key(key),
+                optional,
+                field_type
+            ));
+        }
+        // glTF allows extras on any object, declared or not
+        if !declares_extras(schema) {
+            src.push_str("    extras?: any;\n");
+        }
+        src.push_str("}\n");
+
+        self.types.set(index, src);
+        name
+    }
+}
+
+pub fn generate_typescript(schema: &JsonSchema) -> String {
+    let mut generator = TypeScriptGenerator {
+        types: Types::new(),
+    };
+    generator.interface(schema, "Root");
+    // one named type per definition. objects are interfaces, others are aliases
+    for (name, definition) in &schema.definitions {
+        let field_type = generator.field_type(definition, name);
+        if generator.types.contains(definition) {
+            continue;
+        }
+        if let (alias, Some(index)) = generator
+            .types
+            .reserve(&type_name(definition, name), definition)
+        {
+            let src = format!("type {} = {};\n", alias, field_type);
+            generator.types.set(index, src);
+        }
+    }
+    generator.types.join()
+}
+
+// "byteLength" => "byte_length", "x-id" => "x_id", "type" => "r#type"
+fn snake_case(src: &str) -> String {
+    let mut name = String::new();
+    let mut prev_lower = false;
+    for c in src.chars() {
+        if c.is_ascii_uppercase() {
+            if prev_lower {
+                name.push('_');
+            }
+            name.push(c.to_ascii_lowercase());
+            prev_lower = false;
+        } else if c.is_ascii_alphanumeric() {
+            name.push(c);
+            prev_lower = true;
+        } else {
+            if !name.is_empty() && !name.ends_with('_') {
+                name.push('_');
+            }
+            prev_lower = false;
+        }
+    }
+    let mut name = name.trim_end_matches('_').to_string();
+    if name.is_empty() {
+        name.push_str("field");
+    } else if name.starts_with(|c: char| c.is_ascii_digit()) {
+        name.insert(0, '_');
+    }
+    match name.as_str() {
+        // not allowed as raw identifiers
+        "self" | "super" | "crate" => {
+            name.push('_');
+            name
+        }
+        "as" | "async" | "await" | "break" | "const" | "continue" | "dyn" | "else" | "enum"
+        | "extern" | "false" | "fn" | "for" | "if" | "impl" | "in" | "let" | "loop" | "match"
+        | "mod" | "move" | "mut" | "pub" | "ref" | "return" | "static" | "struct" | "trait"
+        | "true" | "type" | "unsafe" | "use" | "where" | "while" | "abstract" | "become"
+        | "box" | "do" | "final" | "macro" | "override" | "priv" | "try" | "typeof" | "unsized"
+        | "virtual" | "yield" => format!("r#{}", name),
+        _ => name,
+    }
+}
+
+struct RustGenerator<'a> {
+    types: Types<'a>,
+}
+
+impl<'a> RustGenerator<'a> {
+    fn field_type(&mut self, schema: &'a JsonSchema, fallback: &str) -> String {
+        match schema.json_type.as_deref().unwrap_or("") {
+            "string" => "String".to_string(),
+            "integer" => "i64".to_string(),
+            "number" => "f64".to_string(),
+            "boolean" => "bool".to_string(),
+            "array" => match &schema.items {
+                Some(items) => format!("Vec<{}>", self.field_type(items, fallback)),
+                None => "Vec<serde_json::Value>".to_string(),
+            },
+            "object" if schema.properties.is_empty() && schema.reference.is_none() => {
+                "std::collections::HashMap<String, serde_json::Value>".to_string()
+            }
+            "object" if !schema.properties.is_empty() => self.structure(schema, fallback),
+            _ => match &schema.ref_schema {
+                Some(ref_schema) => self.field_type(ref_schema, &type_name(schema, fallback)),
+                None if schema.reference.is_some() => type_name(schema, fallback),
+                None => "serde_json::Value".to_string(),
+            },
+        }
+    }
+
+    fn structure(&mut self, schema: &'a JsonSchema, fallback: &str) -> String {
+        let name = match schema.title.as_deref().filter(|title| !title.is_empty()) {
+            Some(title) => pascal_case(title),
+            None => pascal_case(fallback),
+        };
+        let (name, index) = self.types.reserve(&name, schema);
+        let index = match index {
+            Some(index) => index,
+            None => return name,
+        };
+
+        let mut src = String::new();
+        if let Some(description) = &schema.description {
+            for line in description.lines() {
+                src.push_str(&format!("/// {}\n", line));
+            }
+        }
+        src.push_str("#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]\n");
+        src.push_str(&format!("pub struct {} {{\n", name));
+        // no inheritance. the base is flattened into this struct
+        if let Some(reference) = &schema.reference {
+            let base = match &schema.ref_schema {
+                Some(ref_schema) => self.structure(ref_schema, ref_name(reference)),
+                None => pascal_case(ref_name(reference)),
+            };
+            src.push_str(&format!("    #[serde(flatten)]\n    pub base: {},\n", base));
+        }
+        for (key, property) in &schema.properties {
+            if let Some(description) = &property.description {
+                for line in description.lines() {
+                    src.push_str(&format!("    /// {}\n", line));
+                }
+            }
+            let field = snake_case(key);
+            let optional = !schema.required.contains(key);
+            let mut attributes = Vec::new();
+            if field.trim_start_matches("r#") != key {
+                attributes.push(format!("rename = \"{}\"", key.escape_default()));
+            }
+            // readOnly is only received, writeOnly is only sent
+            if property.read_only {
+                attributes.push("skip_serializing".to_string());
+            } else if optional {
+                attributes.push("skip_serializing_if = \"Option::is_none\"".to_string());
+            }
+            if property.write_only {
+                attributes.push("skip_deserializing".to_string());
+            }
+            if !attributes.is_empty() {
+                src.push_str(&format!("    #[serde({})]\n", attributes.join(", ")));
+            }
+            // glTF extras is application data of any shape
+            let field_type = if key == "extras" {
+                "serde_json::Value".to_string()
+            } else {
+                self.field_type(property, &format!("{}_{}", name, key))
+            };
+            if optional {
+                src.push_str(&format!("    pub {}: Option<{}>,\n", field, field_type));
+            } else {
+                src.push_str(&format!("    pub {}: {},\n", field, field_type));
+            }
+        }
+        // glTF allows extras on any object, declared or not
+        if !declares_extras(schema) {
+            src.push_str("    #[serde(skip_serializing_if = \"Option::is_none\")]\n");
+            src.push_str("    pub extras: Option<serde_json::Value>,\n");
+        }
+        src.push_str("}\n");
+
+        self.types.set(index, src);
+        name
+    }
+}
+
+// serde structs for the schema. the output refers to the serde and serde_json crates
+pub fn generate_rust(schema: &JsonSchema) -> String {
+    let mut generator = RustGenerator {
+        types: Types::new(),
+    };
+    generator.structure(schema, "Root");
+    // one named type per definition. objects are structs, others are aliases
+    for (name, definition) in &schema.definitions {
+        let field_type = generator.field_type(definition, name);
+        if generator.types.contains(definition) {
+            continue;
+        }
+        if let (alias, Some(index)) = generator
+            .types
+            .reserve(&type_name(definition, name), definition)
+        {
+            let src = format!("pub type {} = {};\n", alias, field_type);
+            generator.types.set(index, src);
+        }
+    }
+    generator.types.join()
+}
+
+#[test]
+fn generate_typescript_tests() {
+    use super::parser::JsonSchemaParser;
+
+    let parser = JsonSchemaParser::from_str(
+        r##"{
+    "title": "Mesh Primitive",
+    "type": "object",
+    "allOf": [ { "$ref": "glTFProperty.schema.json" } ],
+    "properties": {
+        "mode": { "type": "integer", "description": "The topology type." },
+        "name": { "type": "string" },
+        "targets": { "type": "array", "items": { "type": "number" } },
+        "visible": { "type": "boolean" },
+        "extensions": { "type": "object" },
+        "extras": { "$ref": "extras.schema.json" },
+        "material": {
+            "title": "Material",
+            "type": "object",
+            "properties": { "doubleSided": { "type": "boolean" } }
+        }
+    },
+    "required": [ "mode" ]
+}"##,
+    );
+
+    let ts = generate_typescript(&parser.root);
+    assert!(ts.contains("interface MeshPrimitive extends GlTFProperty {\n"));
+    assert!(ts.contains("    /** The topology type. */\n    mode: number;\n"));
+    assert!(ts.contains("    name?: string;\n"));
+    assert!(ts.contains("    targets?: number[];\n"));
+    assert!(ts.contains("    visible?: boolean;\n"));
+    assert!(ts.contains("    extensions?: { [key: string]: any };\n"));
+    assert!(ts.contains("    extras?: any;\n"));
+    assert!(ts.contains("    material?: Material;\n"));
+    assert!(ts.contains("interface Material {\n    doubleSided?: boolean;\n    extras?: any;\n}\n"));
+}
+
+#[test]
+fn read_only_tests() {
+    use super::parser::JsonSchemaParser;
+
+    let parser = JsonSchemaParser::from_str(
+        r##"{
+    "title": "Buffer",
+    "type": "object",
+    "properties": {
+        "byteLength": { "type": "integer", "readOnly": true },
+        "password": { "type": "string", "writeOnly": true }
+    }
+}"##,
+    );
+    let buffer = &parser.root;
+    assert!(buffer.property("byteLength").unwrap().read_only);
+    assert!(!buffer.property("byteLength").unwrap().write_only);
+    assert!(buffer.property("password").unwrap().write_only);
+
+    let ts = generate_typescript(buffer);
+    assert!(ts.contains("    readonly byteLength?: number;\n"));
+    assert!(ts.contains("    password?: string;\n"));
+}
+
+#[test]
+fn allof_property_tests() {
+    use super::parser::JsonSchemaParser;
+
+    let dir = std::env::temp_dir().join(format!("jsonprops_codegen_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("accessor.sparse.schema.json"),
+        r##"{ "type": "object", "properties": { "count": { "type": "integer" } } }"##,
+    )
+    .unwrap();
+    std::fs::write(dir.join("glTFid.schema.json"), r##"{ "type": "integer" }"##).unwrap();
+    std::fs::write(
+        dir.join("accessor.schema.json"),
+        r##"{
+    "title": "Accessor",
+    "type": "object",
+    "properties": {
+        "bufferView": { "allOf": [ { "$ref": "glTFid.schema.json" } ] },
+        "sparse": {
+            "type": "object",
+            "description": "Sparse storage.",
+            "allOf": [ { "$ref": "accessor.sparse.schema.json" } ]
+        },
+        "extended": {
+            "type": "object",
+            "allOf": [ { "$ref": "accessor.sparse.schema.json" } ],
+            "properties": { "name": { "type": "string" } }
+        }
+    }
+}"##,
+    )
+    .unwrap();
+    let parser = JsonSchemaParser::from_path(&dir.join("accessor.schema.json")).unwrap();
+
+    let ts = generate_typescript(&parser.root);
+    assert!(ts.contains("    bufferView?: number;\n"));
+    assert!(ts.contains("    /** Sparse storage. */\n    sparse?: AccessorSparse;\n"));
+    assert!(ts.contains("interface AccessorSparse {\n    count?: number;\n    extras?: any;\n}\n"));
+    assert!(ts.contains("    extended?: AccessorExtended;\n"));
+    assert!(ts.contains("interface AccessorExtended extends AccessorSparse {\n"));
+
+    // unresolved reference
+    let parser = JsonSchemaParser::from_str(
+        r##"{ "title": "Accessor", "properties": {
+    "sparse": { "allOf": [ { "$ref": "accessor.sparse.schema.json" } ] } } }"##,
+    );
+    assert!(generate_typescript(&parser.root).contains("    sparse?: AccessorSparse;\n"));
+}
+
+#[test]
+fn type_name_tests() {
+    let titled = |title: &str| {
+        let mut schema = JsonSchema::new();
+        schema.title = Some(title.to_string());
+        type_name(&schema, "fallback")
+    };
+    assert_eq!("MeshPrimitive", titled("Mesh Primitive"));
+    assert_eq!(
+        "KHRMaterialsUnlitGlTFExtension",
+        titled("KHR_materials_unlit glTF extension")
+    );
+    assert_eq!("AnimationChannelTarget", titled("Animation Channel-Target"));
+    assert_eq!("_2DTexture", titled("2D texture"));
+    assert_eq!("Accessor", titled("  accessor  "));
+    assert_eq!("Root", titled("!!"));
+
+    // no title
+    assert_eq!("Fallback", titled(""));
+    let mut schema = JsonSchema::new();
+    schema.reference = Some("accessor.sparse.indices.schema.json".to_string());
+    assert_eq!("AccessorSparseIndices", type_name(&schema, "fallback"));
+    assert_eq!("Extras", type_name(&JsonSchema::new(), "extras"));
+}
+
+#[test]
+fn definitions_tests() {
+    use super::parser::JsonSchemaParser;
+
+    let parser = JsonSchemaParser::from_str(
+        r##"{
+    "title": "Skin",
+    "type": "object",
+    "properties": {
+        "joints": { "type": "array", "items": { "$ref": "#/definitions/joint" } },
+        "bind": { "$ref": "#/$defs/bind" }
+    },
+    "definitions": { "joint": { "type": "integer" } },
+    "$defs": {
+        "bind": { "type": "object", "properties": { "matrix": { "type": "array", "items": { "type": "number" } } } }
+    }
+}"##,
+    );
+
+    let ts = generate_typescript(&parser.root);
+    assert!(ts.contains("    joints?: number[];\n"));
+    assert!(ts.contains("    bind?: Bind;\n"));
+    assert!(ts.contains("interface Bind {\n    matrix?: number[];\n    extras?: any;\n}\n"));
+    assert!(ts.contains("type Joint = number;\n"));
+    assert_eq!(1, ts.matches("interface Bind").count());
+
+    let rs = generate_rust(&parser.root);
+    assert!(rs.contains("    pub joints: Option<Vec<i64>>,\n"));
+    assert!(rs.contains("    pub bind: Option<Bind>,\n"));
+    assert!(rs.contains("pub struct Bind {\n    #[serde(skip_serializing_if = \"Option::is_none\")]\n    pub matrix: Option<Vec<f64>>,\n"));
+    assert!(rs.contains("pub type Joint = i64;\n"));
+    assert_eq!(1, rs.matches("pub struct Bind").count());
+}
+
+#[test]
+fn name_collision_tests() {
+    use super::parser::JsonSchemaParser;
+
+    // a definition and a property title that share a name are different types
+    let parser = JsonSchemaParser::from_str(
+        r##"{
+    "title": "Skin",
+    "type": "object",
+    "properties": {
+        "bind": { "$ref": "#/$defs/bind" },
+        "binding": { "title": "Bind", "type": "object", "properties": { "joint": { "type": "integer" } } },
+        "joint": { "title": "Joint", "type": "object", "properties": { "node": { "type": "integer" } } }
+    },
+    "$defs": {
+        "bind": { "type": "object", "properties": { "matrix": { "type": "array", "items": { "type": "number" } } } },
+        "joint": { "type": "integer" }
+    }
+}"##,
+    );
+
+    let ts = generate_typescript(&parser.root);
+    assert!(ts.contains("    bind?: Bind;\n"));
+    assert!(ts.contains("    binding?: Bind2;\n"));
+    assert!(ts.contains("interface Bind {\n    matrix?: number[];\n"));
+    assert!(ts.contains("interface Bind2 {\n    joint?: number;\n"));
+    assert!(ts.contains("    joint?: Joint;\n"));
+    assert!(ts.contains("interface Joint {\n    node?: number;\n"));
+    assert!(ts.contains("type Joint2 = number;\n"));
+
+    let rs = generate_rust(&parser.root);
+    assert!(rs.contains("    pub binding: Option<Bind2>,\n"));
+    assert!(rs.contains("pub struct Bind {\n"));
+    assert!(rs.contains("pub struct Bind2 {\n"));
+    assert!(rs.contains("pub type Joint2 = i64;\n"));
+}
+
+#[test]
+fn generate_rust_tests() {
+    use super::parser::JsonSchemaParser;
+
+    let parser = JsonSchemaParser::from_str(
+        r##"{
+    "title": "Buffer",
+    "description": "A buffer points to binary geometry.",
+    "type": "object",
+    "allOf": [ { "$ref": "glTFChildOfRootProperty.schema.json" } ],
+    "properties": {
+        "byteLength": { "type": "integer", "readOnly": true },
+        "password": { "type": "string", "writeOnly": true },
+        "uri": { "type": "string", "description": "The uri of the buffer." },
+        "type": { "type": "number" },
+        "views": { "type": "array", "items": { "type": "boolean" } },
+        "extensions": { "type": "object" }
+    },
+    "required": [ "byteLength", "type" ]
+}"##,
+    );
+
+    let rs = generate_rust(&parser.root);
+    assert!(rs.starts_with("/// A buffer points to binary geometry.\n#[derive("));
+    assert!(rs.contains("pub struct Buffer {\n"));
+    assert!(rs.contains("    #[serde(flatten)]\n    pub base: GlTFChildOfRootProperty,\n"));
+    assert!(rs.contains(
+        "    #[serde(rename = \"byteLength\", skip_serializing)]\n    pub byte_length: i64,\n"
+    ));
+    assert!(rs.contains(
+        "    #[serde(skip_serializing_if = \"Option::is_none\", skip_deserializing)]\n    pub password: Option<String>,\n"
+    ));
+    assert!(rs.contains("    /// The uri of the buffer.\n"));
+    assert!(rs.contains("    pub uri: Option<String>,\n"));
+    assert!(rs.contains("    pub r#type: f64,\n"));
+    assert!(rs.contains("    pub views: Option<Vec<bool>>,\n"));
+    assert!(rs.contains(
+        "    pub extensions: Option<std::collections::HashMap<String, serde_json::Value>>,\n"
+    ));
+}
+
+#[test]
+fn snake_case_tests() {
+    assert_eq!("byte_length", snake_case("byteLength"));
+    assert_eq!("khr_materials_unlit", snake_case("KHR_materials_unlit"));
+    assert_eq!("x_id", snake_case("x-id"));
+    assert_eq!("r#type", snake_case("type"));
+    assert_eq!("self_", snake_case("self"));
+    assert_eq!("_2d", snake_case("2d"));
+    assert_eq!("field", snake_case("--"));
+}
+
+#[test]
+fn extras_tests() {
+    use super::parser::JsonSchemaParser;
+
+    // extras is emitted whether or not the schema lists it
+    let parser = JsonSchemaParser::from_str(
+        r##"{ "title": "Sampler", "type": "object", "properties": { "wrapS": { "type": "integer" } } }"##,
+    );
+    let ts = generate_typescript(&parser.root);
+    assert!(ts.contains("    wrapS?: number;\n    extras?: any;\n}\n"));
+    let rs = generate_rust(&parser.root);
+    assert!(rs.contains("    pub extras: Option<serde_json::Value>,\n}\n"));
+
+    let parser = JsonSchemaParser::from_str(
+        r##"{ "title": "Sampler", "type": "object", "properties": { "extras": { "type": "object" } } }"##,
+    );
+    let ts = generate_typescript(&parser.root);
+    assert_eq!(1, ts.matches("extras?: any;").count());
+    let rs = generate_rust(&parser.root);
+    assert_eq!(
+        1,
+        rs.matches("pub extras: Option<serde_json::Value>,").count()
+    );
+}
+
+#[test]
+fn property_key_tests() {
+    use super::parser::JsonSchemaParser;
+
+    let parser = JsonSchemaParser::from_str(
+        r##"{
+    "title": "Node",
+    "description": "Ends with */ in the middle.",
+    "type": "object",
+    "properties": {
+        "x-id": { "type": "integer", "description": "/* nested */" },
+        "$type": { "type": "string" },
+        "_name2": { "type": "string" },
+        "2d": { "type": "boolean" },
+        "a b": { "type": "boolean" },
+        "a\"b": { "type": "boolean" }
+    }
+}"##,
+    );
+
+    let ts = generate_typescript(&parser.root);
+    assert!(ts.starts_with("/** Ends with *\\/ in the middle. */\ninterface Node {\n"));
+    assert!(ts.contains("    /** /* nested *\\/ */\n    \"x-id\"?: number;\n"));
+    assert!(ts.contains("    $type?: string;\n"));
+    assert!(ts.contains("    _name2?: string;\n"));
+    assert!(ts.contains("    \"2d\"?: boolean;\n"));
+    assert!(ts.contains("    \"a b\"?: boolean;\n"));
+    assert!(ts.contains("    \"a\\\"b\"?: boolean;\n"));
+}
+
+#[test]
+fn base_extras_tests() {
+    use super::parser::JsonSchemaParser;
+
+    // the base declares extras, the derived type inherits it
+    let parser = JsonSchemaParser::from_str(
+        r##"{
+    "title": "Buffer",
+    "type": "object",
+    "allOf": [ { "$ref": "#/definitions/glTFProperty" } ],
+    "properties": { "byteLength": { "type": "integer" } },
+    "definitions": {
+        "glTFProperty": {
+            "type": "object",
+            "properties": { "extensions": { "type": "object" }, "extras": {} }
+        }
+    }
+}"##,
+    );
+
+    let rs = generate_rust(&parser.root);
+    assert!(rs.contains("    #[serde(flatten)]\n    pub base: GlTFProperty,\n"));
+    assert!(rs.contains("pub struct GlTFProperty {\n"));
+    assert_eq!(
+        1,
+        rs.matches("pub extras: Option<serde_json::Value>,").count()
+    );
+
+    let ts = generate_typescript(&parser.root);
+    assert!(ts.contains("interface Buffer extends GlTFProperty {\n    byteLength?: number;\n}\n"));
+    assert_eq!(1, ts.matches("extras?: any;").count());
+}
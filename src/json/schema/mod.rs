@@ -1 +1,3 @@
+pub mod codegen;
 pub mod parser;
+pub mod validate;
@@ -1,80 +1,924 @@
-use super::super::node::{JsonNode, JsonNodeError, JsonNodeResult};
-use super::super::parser::JsonParser;
+use super::super::node::{ErrorKind, JsonNode, JsonNodeError, JsonNodeResult};
+use super::super::parser::{JsonParser, Number, ParseError};
+use super::super::string::unescape;
+use super::super::value::OwnedValue;
+use alloc::borrow::Cow;
+use core::convert::TryFrom;
+use std::path::{Path, PathBuf};
 
+// keywords that changed meaning between drafts
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum SchemaDraft {
+    #[default]
+    Draft7, // glTF
+    Draft2020_12,
+}
+
+impl SchemaDraft {
+    // from the "$schema" uri. glTF 2.0 schemas use draft-04,
+    // which reads items and additionalItems the same way as draft-07
+    pub fn from_uri(uri: &str) -> Option<SchemaDraft> {
+        if uri.contains("draft-04") || uri.contains("draft-06") || uri.contains("draft-07") {
+            Some(SchemaDraft::Draft7)
+        } else if uri.contains("2020-12") {
+            Some(SchemaDraft::Draft2020_12)
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Clone, PartialEq)]
 pub struct JsonSchema {
-    pub title: String,
-    pub description: String,
-    pub json_type: String,
+    pub draft: SchemaDraft,
+    // "$schema" as written
+    pub dialect: Option<String>,
+    // false schema. nothing is valid
+    pub is_false: bool,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    pub json_type: Option<String>,
+    pub reference: Option<String>,
+    // loaded from reference
+    pub ref_schema: Option<Box<JsonSchema>>,
+    // allOf other than a single $ref, which goes to reference
+    pub all_of: Vec<JsonSchema>,
+    pub properties: Vec<(String, JsonSchema)>,
+    // "definitions" or "$defs". targets of "#/definitions/x" and "#/$defs/x".
+    // (name, schema) pairs rather than a map, so codegen emits them in document order
+    pub definitions: Vec<(String, JsonSchema)>,
+    // checked with the regex feature
+    pub pattern_properties: Vec<(String, JsonSchema)>,
+    // members not in properties and pattern_properties
+    pub additional_properties: Option<Box<JsonSchema>>,
+    pub required: Vec<String>,
+    pub items: Option<Box<JsonSchema>>,
+    // 2020-12 prefixItems or draft-07 array form items. items applies to the rest
+    pub prefix_items: Vec<JsonSchema>,
+    // at least min_contains (default 1) elements match
+    pub contains: Option<Box<JsonSchema>>,
+    pub min_contains: Option<usize>,
+    pub max_contains: Option<usize>,
+    pub default_value: Option<OwnedValue>,
+    pub examples: Vec<OwnedValue>,
+    // checked with the regex feature
+    pub pattern: Option<String>,
+    pub property_names: Option<Box<JsonSchema>>,
+    // annotations. not checked by validate
+    pub read_only: bool,
+    pub write_only: bool,
+    // keywords this parser does not read, with their values. the cli lists them
+    pub unknown_keywords: Vec<(String, OwnedValue)>,
+}
+
+impl Default for JsonSchema {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl JsonSchema {
     pub fn new() -> JsonSchema {
         JsonSchema {
-            title: String::new(),
-            description: String::new(),
-            json_type: String::new(),
+            draft: SchemaDraft::default(),
+            dialect: None,
+            is_false: false,
+            title: None,
+            description: None,
+            json_type: None,
+            reference: None,
+            ref_schema: None,
+            all_of: Vec::new(),
+            properties: Vec::new(),
+            definitions: Vec::new(),
+            pattern_properties: Vec::new(),
+            additional_properties: None,
+            required: Vec::new(),
+            items: None,
+            prefix_items: Vec::new(),
+            contains: None,
+            min_contains: None,
+            max_contains: None,
+            default_value: None,
+            examples: Vec::new(),
+            pattern: None,
+            property_names: None,
+            read_only: false,
+            write_only: false,
+            unknown_keywords: Vec::new(),
         }
     }
 
     // [ { "$ref": "glTFProperty.schema.json" } ]
-    pub fn set_allof<'a>(&self, v: JsonNode<'a>) -> JsonNodeResult<'a> {
-        if v.array_len() == Some(1) {
-            if let Ok(node) = v.get(0) {
-                if let Some(len) = node.object_len() {
-                    if len == 1 {
-                        let value = node.key("$ref")?;
-                        if let Some(text) = value.get_string() {
-                            println!("{}", text);
-                            return Ok(value);
+    pub fn set_allof<'a>(&mut self, v: JsonNode<'a>) -> JsonNodeResult<'a> {
+        let node = v.get(0)?;
+        let value = node.key("$ref")?;
+        let text = value.get_string_typed()?;
+        if v.array_len() == Some(1) && node.object_len() == Some(1) {
+            self.reference = Some(text.to_string());
+            return Ok(value);
+        }
+        Err(JsonNodeError::new(&v, ErrorKind::Unsupported))
+    }
+
+    // a root schema, with "#/definitions/x" and "#/$defs/x" resolved
+    pub fn parse(node: JsonNode) -> JsonSchema {
+        let mut schema = JsonSchema::parse_draft(node, SchemaDraft::default());
+        schema.resolve_definitions();
+        schema
+    }
+
+    // "$schema" overrides draft
+    pub fn parse_draft(node: JsonNode, draft: SchemaDraft) -> JsonSchema {
+        let mut schema = JsonSchema::new();
+        schema.dialect = node
+            .try_key("$schema")
+            .and_then(|v| v.get_str())
+            .map(|s| s.into_owned());
+        schema.draft = schema
+            .dialect
+            .as_deref()
+            .and_then(SchemaDraft::from_uri)
+            .unwrap_or(draft);
+        let draft = schema.draft;
+        schema.is_false = node.get_bool() == Some(false);
+        let mut additional_items = None;
+        let get_str = |key| {
+            node.try_key(key)
+                .and_then(|v| v.get_str())
+                .map(|s| s.into_owned())
+        };
+        schema.title = get_str("title");
+        schema.description = get_str("description");
+        schema.json_type = get_str("type");
+        for (k, v) in node.object_iter() {
+            match k {
+                "$schema" | "title" | "description" | "type" => {}
+                "$ref" => {
+                    if let Some(reference) = v.get_string() {
+                        schema.reference = Some(reference.to_string());
+                    }
+                }
+                "allOf" => {
+                    if schema.set_allof(v).is_err() {
+                        schema.all_of = v
+                            .array_iter()
+                            .map(|branch| JsonSchema::parse_draft(branch, draft))
+                            .collect();
+                    }
+                }
+                "required" => {
+                    for item in v.array_iter() {
+                        if let Some(name) = item.get_string() {
+                            schema.required.push(name.to_string());
+                        }
+                    }
+                }
+                "dependencies" if draft == SchemaDraft::Draft7 => {}
+                "dependentRequired" | "dependentSchemas" if draft == SchemaDraft::Draft2020_12 => {}
+                "properties" => {
+                    for (name, property) in v.object_iter() {
+                        schema
+                            .properties
+                            .push((key_name(name), JsonSchema::parse_draft(property, draft)));
+                    }
+                }
+                "definitions" | "$defs" => {
+                    for (name, definition) in v.object_iter() {
+                        schema
+                            .definitions
+                            .push((key_name(name), JsonSchema::parse_draft(definition, draft)));
+                    }
+                }
+                "patternProperties" => {
+                    for (pattern, property) in v.object_iter() {
+                        schema
+                            .pattern_properties
+                            .push((key_name(pattern), JsonSchema::parse_draft(property, draft)));
+                    }
+                }
+                "additionalProperties" => {
+                    schema.additional_properties =
+                        Some(Box::new(JsonSchema::parse_draft(v, draft)));
+                }
+                "items" if draft == SchemaDraft::Draft7 && v.array_len().is_some() => {
+                    for item in v.array_iter() {
+                        schema
+                            .prefix_items
+                            .push(JsonSchema::parse_draft(item, draft));
+                    }
+                }
+                "items" => {
+                    schema.items = Some(Box::new(JsonSchema::parse_draft(v, draft)));
+                }
+                "pattern" => {
+                    schema.pattern = v.get_str().map(|s| s.into_owned());
+                }
+                "propertyNames" => {
+                    schema.property_names = Some(Box::new(JsonSchema::parse_draft(v, draft)));
+                }
+                "contains" => {
+                    schema.contains = Some(Box::new(JsonSchema::parse_draft(v, draft)));
+                }
+                // negative counts are ignored
+                "minContains" => {
+                    schema.min_contains = v.get_int().and_then(|n| usize::try_from(n).ok());
+                }
+                "maxContains" => {
+                    schema.max_contains = v.get_int().and_then(|n| usize::try_from(n).ok());
+                }
+                "additionalItems" if draft == SchemaDraft::Draft7 => {
+                    additional_items = Some(Box::new(JsonSchema::parse_draft(v, draft)));
+                }
+                "prefixItems" if draft == SchemaDraft::Draft2020_12 => {
+                    for item in v.array_iter() {
+                        schema
+                            .prefix_items
+                            .push(JsonSchema::parse_draft(item, draft));
+                    }
+                }
+                "default" => {
+                    schema.default_value = Some(OwnedValue::from_node(&v));
+                }
+                "readOnly" => {
+                    schema.read_only = v.get_bool() == Some(true);
+                }
+                "writeOnly" => {
+                    schema.write_only = v.get_bool() == Some(true);
+                }
+                "examples" => {
+                    schema.examples = v.array_iter().map(|e| OwnedValue::from_node(&e)).collect();
+                }
+                _ => schema
+                    .unknown_keywords
+                    .push((key_name(k), OwnedValue::from_node(&v))),
+            }
+        }
+        // additionalItems is used only with the array form
+        if !schema.prefix_items.is_empty() && schema.items.is_none() {
+            schema.items = additional_items;
+        }
+        schema
+    }
+
+    pub fn property(&self, name: &str) -> Option<&JsonSchema> {
+        self.properties
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v)
+    }
+
+    // minimal document. required properties are filled with default or zero value
+    pub fn default_instance(&self) -> OwnedValue {
+        if let Some(value) = &self.default_value {
+            return value.clone();
+        }
+
+        match self.json_type.as_deref().unwrap_or("") {
+            "object" => {
+                let mut members = Vec::new();
+                self.required_members(self, &mut members);
+                OwnedValue::Object(members)
+            }
+            "array" => OwnedValue::Array(Vec::new()),
+            "string" => OwnedValue::String(String::new()),
+            "integer" => OwnedValue::Number(Number::Int(0)),
+            "number" => OwnedValue::Number(Number::Float(0.0)),
+            "boolean" => OwnedValue::Bool(false),
+            _ => match &self.ref_schema {
+                Some(ref_schema) => ref_schema.default_instance(),
+                None => OwnedValue::Null,
+            },
+        }
+    }
+
+    // the property here, in the base or in an allOf branch
+    fn find_property(&self, name: &str) -> Option<&JsonSchema> {
+        self.property(name).or_else(|| {
+            self.ref_schema
+                .as_deref()
+                .into_iter()
+                .chain(&self.all_of)
+                .find_map(|s| s.find_property(name))
+        })
+    }
+
+    // base and allOf members first. a name is added once, typed by the property root finds
+    fn required_members(&self, root: &JsonSchema, members: &mut Vec<(String, OwnedValue)>) {
+        for base in self.ref_schema.as_deref().into_iter().chain(&self.all_of) {
+            match &base.default_value {
+                Some(OwnedValue::Object(defaults)) => {
+                    for (name, value) in defaults {
+                        if !members.iter().any(|(k, _)| k == name) {
+                            members.push((name.clone(), value.clone()));
                         }
                     }
                 }
+                _ => base.required_members(root, members),
+            }
+        }
+        for name in &self.required {
+            if !members.iter().any(|(k, _)| k == name) {
+                let value = root
+                    .find_property(name)
+                    .map_or(OwnedValue::Null, |s| s.default_instance());
+                members.push((name.clone(), value));
+            }
+        }
+    }
+
+    fn resolve_refs(&mut self, dir: &Path, loading: &mut Vec<PathBuf>) -> std::io::Result<()> {
+        if let Some(reference) = &self.reference {
+            // local reference is not a file
+            if !reference.starts_with('#') {
+                let path = dir.join(reference);
+                if !loading.contains(&path) {
+                    self.ref_schema = Some(Box::new(JsonSchema::load(&path, self.draft, loading)?));
+                }
+            }
+        }
+        for child in self.subschemas_mut() {
+            child.resolve_refs(dir, loading)?;
+        }
+        Ok(())
+    }
+
+    // nested schemas other than ref_schema
+    fn subschemas_mut(&mut self) -> impl Iterator<Item = &mut JsonSchema> {
+        self.all_of
+            .iter_mut()
+            .chain(self.properties.iter_mut().map(|(_, s)| s))
+            .chain(self.definitions.iter_mut().map(|(_, s)| s))
+            .chain(self.pattern_properties.iter_mut().map(|(_, s)| s))
+            .chain(self.additional_properties.as_deref_mut())
+            .chain(self.items.as_deref_mut())
+            .chain(self.prefix_items.iter_mut())
+            .chain(self.contains.as_deref_mut())
+            .chain(self.property_names.as_deref_mut())
+    }
+
+    // ref_schema of "#/definitions/x" and "#/$defs/x" is a copy of the definition in this root.
+    // a definition that refers to itself is resolved once, the inner reference is left as is
+    fn resolve_definitions(&mut self) {
+        let definitions = self.definitions.clone();
+        self.resolve_local_refs(&definitions, &mut Vec::new());
+    }
+
+    fn resolve_local_refs(
+        &mut self,
+        definitions: &[(String, JsonSchema)],
+        resolving: &mut Vec<String>,
+    ) {
+        let name = self.reference.as_deref().and_then(|reference| {
+            reference
+                .strip_prefix("#/definitions/")
+                .or_else(|| reference.strip_prefix("#/$defs/"))
+        });
+        if let Some(name) = name {
+            let definition = definitions.iter().find(|(k, _)| k == name);
+            if let (Some((_, definition)), false) =
+                (definition, resolving.iter().any(|n| n == name))
+            {
+                let mut definition = definition.clone();
+                resolving.push(name.to_string());
+                definition.resolve_local_refs(definitions, resolving);
+                resolving.pop();
+                self.ref_schema = Some(Box::new(definition));
             }
         }
-        Err(JsonNodeError {})
+        for child in self.subschemas_mut() {
+            child.resolve_local_refs(definitions, resolving);
+        }
+    }
+
+    fn load(
+        path: &Path,
+        draft: SchemaDraft,
+        loading: &mut Vec<PathBuf>,
+    ) -> std::io::Result<JsonSchema> {
+        let src = std::fs::read_to_string(path)?;
+        // a broken file is bad data, not a bug
+        let parser = JsonParser::try_process(&src).map_err(|e| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), e),
+            )
+        })?;
+        let mut schema = JsonSchema::parse_draft(JsonNode::new(&parser), draft);
+        schema.resolve_definitions();
+
+        loading.push(path.to_path_buf());
+        let dir = path.parent().unwrap_or_else(|| Path::new(""));
+        schema.resolve_refs(dir, loading)?;
+        loading.pop();
+
+        Ok(schema)
     }
 }
 
+// "a\/b" => "a/b". a pattern "\\d" is the regex \d
+fn key_name(key: &str) -> String {
+    unescape(key).map_or_else(|| key.to_string(), Cow::into_owned)
+}
+
+fn collect_refs_to(node: JsonNode, refs: &mut Vec<String>) {
+    for (key, value) in node.object_iter() {
+        match (key, value.get_str()) {
+            ("$ref", Some(reference)) => refs.push(reference.into_owned()),
+            _ => collect_refs_to(value, refs),
+        }
+    }
+    for item in node.array_iter() {
+        collect_refs_to(item, refs);
+    }
+}
+
+#[derive(Debug)]
+pub enum LoadOrderError {
+    Io(PathBuf, std::io::Error),
+    // the loader returned text that is not json
+    Parse(PathBuf, ParseError),
+    // the first and the last path are the same
+    Cycle(Vec<PathBuf>),
+}
+
+fn load_order_visit(
+    path: &Path,
+    loader: &mut impl FnMut(&Path) -> std::io::Result<String>,
+    loading: &mut Vec<PathBuf>,
+    order: &mut Vec<PathBuf>,
+) -> Result<(), LoadOrderError> {
+    if order.iter().any(|p| p == path) {
+        return Ok(());
+    }
+    if let Some(i) = loading.iter().position(|p| p == path) {
+        let mut cycle = loading[i..].to_vec();
+        cycle.push(path.to_path_buf());
+        return Err(LoadOrderError::Cycle(cycle));
+    }
+
+    let src = loader(path).map_err(|e| LoadOrderError::Io(path.to_path_buf(), e))?;
+    loading.push(path.to_path_buf());
+    let dir = path.parent().unwrap_or_else(|| Path::new(""));
+    let refs = JsonSchemaParser::collect_refs(&src)
+        .map_err(|e| LoadOrderError::Parse(path.to_path_buf(), e))?;
+    for reference in refs {
+        // "file.json#/definitions/x" depends on file.json
+        let file = reference.split('#').next().unwrap_or("");
+        if !file.is_empty() {
+            load_order_visit(&dir.join(file), loader, loading, order)?;
+        }
+    }
+    loading.pop();
+    order.push(path.to_path_buf());
+    Ok(())
+}
+
 pub struct JsonSchemaParser {
     pub root: JsonSchema,
+    // schemas of glTF extensions by name. see validate
+    pub extensions: Vec<(String, JsonSchema)>,
 }
 
 impl JsonSchemaParser {
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(text: &str) -> JsonSchemaParser {
-        let parser = JsonParser::process(&text);
+        JsonSchemaParser::from_str_draft(text, SchemaDraft::default())
+    }
 
-        let root = JsonNode::new(&parser);
+    // draft is used when "$schema" is absent
+    pub fn from_str_draft(text: &str, draft: SchemaDraft) -> JsonSchemaParser {
+        let parser = JsonParser::process(text);
 
-        // println!("ok");
-        let mut schema = JsonSchema::new();
-        for (k, v) in root.object_iter() {
-            match k {
-                "$schema" => {}
-                "title" => {
-                    if let Some(title) = v.get_string() {
-                        schema.title = title.to_string();
-                    }
-                }
-                "description" => {
-                    if let Some(description) = v.get_string() {
-                        schema.description = description.to_string();
-                    }
-                }
-                "type" => {
-                    if let Some(json_type) = v.get_string() {
-                        schema.json_type = json_type.to_string();
-                    }
-                }
-                "allOf" => {
-                    schema.set_allof(v).unwrap();
-                }
-                "required" => {}
-                "dependencies" => {}
-                "properties" => {}
-                _ => println!("{} => {}", k, v),
-            }
+        let mut root = JsonSchema::parse_draft(JsonNode::new(&parser), draft);
+        root.resolve_definitions();
+
+        JsonSchemaParser {
+            root,
+            extensions: Vec::new(),
         }
+    }
+
+    // $ref to other files are loaded relative to path
+    pub fn from_path(path: &Path) -> std::io::Result<JsonSchemaParser> {
+        JsonSchemaParser::from_path_draft(path, SchemaDraft::default())
+    }
+
+    // every "$ref" string in the document, in document order
+    pub fn collect_refs(src: &str) -> Result<Vec<String>, ParseError> {
+        let parser = JsonParser::try_process(src)?;
+        let mut refs = Vec::new();
+        collect_refs_to(JsonNode::new(&parser), &mut refs);
+        Ok(refs)
+    }
+
+    // root and every file it references, dependencies first
+    pub fn load_order(
+        root: &Path,
+        mut loader: impl FnMut(&Path) -> std::io::Result<String>,
+    ) -> Result<Vec<PathBuf>, LoadOrderError> {
+        let mut order = Vec::new();
+        load_order_visit(root, &mut loader, &mut Vec::new(), &mut order)?;
+        Ok(order)
+    }
+
+    // replaces a schema registered with the same name
+    pub fn register_extension(&mut self, name: &str, schema: JsonSchema) {
+        match self.extensions.iter_mut().find(|(k, _)| k == name) {
+            Some((_, registered)) => *registered = schema,
+            None => self.extensions.push((name.to_string(), schema)),
+        }
+    }
 
-        JsonSchemaParser { root: schema }
+    // "$ref" in the extension schema are loaded relative to path
+    pub fn register_extension_path(&mut self, name: &str, path: &Path) -> std::io::Result<()> {
+        let schema = JsonSchema::load(path, self.root.draft, &mut Vec::new())?;
+        self.register_extension(name, schema);
+        Ok(())
     }
+
+    pub fn extension(&self, name: &str) -> Option<&JsonSchema> {
+        self.extensions
+            .iter()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v)
+    }
+
+    pub fn from_path_draft(path: &Path, draft: SchemaDraft) -> std::io::Result<JsonSchemaParser> {
+        Ok(JsonSchemaParser {
+            root: JsonSchema::load(path, draft, &mut Vec::new())?,
+            extensions: Vec::new(),
+        })
+    }
+}
+
+#[test]
+fn default_value_tests() {
+    let parser = JsonSchemaParser::from_str(
+        r##"{
+    "properties": {
+        "name": { "type": "string", "default": "node" },
+        "scale": { "type": "number", "default": 1.5 },
+        "color": { "type": "array", "default": [1, 1, 1] },
+        "none": { "type": "string" }
+    }
+}"##,
+    );
+
+    let root = &parser.root;
+    assert_eq!(
+        Some(&OwnedValue::String("node".to_string())),
+        root.property("name").unwrap().default_value.as_ref()
+    );
+    assert_eq!(
+        Some(&OwnedValue::Number(Number::Float(1.5))),
+        root.property("scale").unwrap().default_value.as_ref()
+    );
+    assert_eq!(
+        Some(&OwnedValue::Array(vec![
+            OwnedValue::Number(Number::Int(1));
+            3
+        ])),
+        root.property("color").unwrap().default_value.as_ref()
+    );
+    assert_eq!(None, root.property("none").unwrap().default_value);
+}
+
+#[test]
+fn dialect_tests() {
+    let dialect = |uri: &str| {
+        let src = format!(r##"{{ "$schema": "{}" }}"##, uri);
+        let root = JsonSchemaParser::from_str_draft(&src, SchemaDraft::Draft2020_12).root;
+        (root.dialect, root.draft)
+    };
+    let uri = "http://json-schema.org/draft-07/schema#";
+    assert_eq!((Some(uri.to_string()), SchemaDraft::Draft7), dialect(uri));
+    let uri = "http://json-schema.org/draft-04/schema";
+    assert_eq!((Some(uri.to_string()), SchemaDraft::Draft7), dialect(uri));
+    let uri = "https://json-schema.org/draft/2020-12/schema";
+    assert_eq!(
+        (Some(uri.to_string()), SchemaDraft::Draft2020_12),
+        dialect(uri)
+    );
+    // unknown dialect keeps the given draft
+    let uri = "https://example.com/schema";
+    assert_eq!(
+        (Some(uri.to_string()), SchemaDraft::Draft2020_12),
+        dialect(uri)
+    );
+
+    let root = JsonSchemaParser::from_str("{}").root;
+    assert_eq!((None, SchemaDraft::Draft7), (root.dialect, root.draft));
+}
+
+#[test]
+fn examples_tests() {
+    let parser = JsonSchemaParser::from_str(
+        r##"{
+    "type": "object",
+    "examples": [ { "name": "a" }, { "name": "b", "scale": 2 } ],
+    "properties": { "name": { "type": "string" } }
+}"##,
+    );
+
+    let examples: Vec<String> = parser.root.examples.iter().map(|e| e.to_string()).collect();
+    assert_eq!(
+        vec![r##"{"name":"a"}"##, r##"{"name":"b","scale":2}"##],
+        examples
+    );
+    assert!(parser.root.property("name").unwrap().examples.is_empty());
+}
+
+#[test]
+fn title_tests() {
+    let parser = JsonSchemaParser::from_str(
+        r##"{
+    "title": "root",
+    "properties": {
+        "empty": { "title": "", "description": "", "type": "string" },
+        "none": {}
+    }
+}"##,
+    );
+
+    let root = &parser.root;
+    assert_eq!(Some("root"), root.title.as_deref());
+    assert_eq!(None, root.description);
+    assert_eq!(None, root.json_type);
+    let empty = root.property("empty").unwrap();
+    assert_eq!(Some(""), empty.title.as_deref());
+    assert_eq!(Some(""), empty.description.as_deref());
+    assert_eq!(Some("string"), empty.json_type.as_deref());
+    let none = root.property("none").unwrap();
+    assert_eq!(
+        (None, None),
+        (none.title.as_ref(), none.description.as_ref())
+    );
+}
+
+#[test]
+fn default_instance_tests() {
+    let parser = JsonSchemaParser::from_str(
+        r##"{
+    "type": "object",
+    "properties": {
+        "name": { "type": "string" },
+        "count": { "type": "integer" },
+        "mode": { "type": "integer", "default": 4 },
+        "children": { "type": "array", "items": { "type": "integer" } }
+    },
+    "required": [ "count", "mode", "children" ]
+}"##,
+    );
+
+    assert_eq!(
+        OwnedValue::Object(vec![
+            ("count".to_string(), OwnedValue::Number(Number::Int(0))),
+            ("mode".to_string(), OwnedValue::Number(Number::Int(4))),
+            ("children".to_string(), OwnedValue::Array(Vec::new())),
+        ]),
+        parser.root.default_instance()
+    );
+
+    // required names defined in the base or another allOf branch, and listed twice
+    let parser = JsonSchemaParser::from_str(
+        r##"{
+    "type": "object",
+    "allOf": [
+        { "$ref": "#/definitions/glTFChildOfRootProperty" },
+        { "properties": { "uri": { "type": "string" } }, "required": [ "byteLength" ] }
+    ],
+    "properties": { "byteLength": { "type": "integer" } },
+    "required": [ "byteLength", "name", "uri", "name" ],
+    "definitions": {
+        "glTFChildOfRootProperty": {
+            "type": "object",
+            "properties": { "name": { "type": "string" } },
+            "required": [ "name" ]
+        }
+    }
+}"##,
+    );
+    assert_eq!(
+        OwnedValue::Object(vec![
+            ("name".to_string(), OwnedValue::String(String::new())),
+            ("byteLength".to_string(), OwnedValue::Number(Number::Int(0))),
+            ("uri".to_string(), OwnedValue::String(String::new())),
+        ]),
+        parser.root.default_instance()
+    );
+}
+
+#[test]
+fn default_instance_ref_tests() {
+    let dir = std::env::temp_dir().join(format!("jsonprops_ref_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(
+        dir.join("base.schema.json"),
+        r##"{ "type": "object", "properties": { "name": { "type": "string" } }, "required": [ "name" ] }"##,
+    )
+    .unwrap();
+    std::fs::write(
+        dir.join("root.schema.json"),
+        r##"{ "type": "object", "allOf": [ { "$ref": "base.schema.json" } ], "properties": { "scale": { "type": "number" } }, "required": [ "scale" ] }"##,
+    )
+    .unwrap();
+
+    let parser = JsonSchemaParser::from_path(&dir.join("root.schema.json")).unwrap();
+    assert_eq!(
+        OwnedValue::Object(vec![
+            ("name".to_string(), OwnedValue::String(String::new())),
+            ("scale".to_string(), OwnedValue::Number(Number::Float(0.0))),
+        ]),
+        parser.root.default_instance()
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn definitions_tests() {
+    let parser = JsonSchemaParser::from_str(
+        r##"{
+    "type": "object",
+    "properties": {
+        "weights": { "type": "array", "items": { "$ref": "#/definitions/weight" } },
+        "root": { "$ref": "#/$defs/node" }
+    },
+    "definitions": {
+        "weight": { "type": "number" }
+    },
+    "$defs": {
+        "node": {
+            "type": "object",
+            "properties": { "children": { "type": "array", "items": { "$ref": "#/$defs/node" } } }
+        }
+    }
+}"##,
+    );
+    let root = &parser.root;
+    let names: Vec<&str> = root.definitions.iter().map(|(k, _)| k.as_str()).collect();
+    assert_eq!(vec!["weight", "node"], names);
+
+    let weight = root.property("weights").unwrap().items.as_deref().unwrap();
+    assert_eq!(
+        Some("number"),
+        weight.ref_schema.as_deref().unwrap().json_type.as_deref()
+    );
+
+    // self reference is resolved one level deep
+    let node = root
+        .property("root")
+        .unwrap()
+        .ref_schema
+        .as_deref()
+        .unwrap();
+    let children = node.property("children").unwrap().items.as_deref().unwrap();
+    assert_eq!(Some("#/$defs/node"), children.reference.as_deref());
+    assert!(children.ref_schema.is_none());
+}
+
+#[test]
+fn load_invalid_json_tests() {
+    let dir = std::env::temp_dir().join(format!("jsonprops_invalid_{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("broken.schema.json"), "{ \"type\": ").unwrap();
+    std::fs::write(
+        dir.join("root.schema.json"),
+        r##"{ "allOf": [ { "$ref": "broken.schema.json" } ] }"##,
+    )
+    .unwrap();
+
+    for name in ["root.schema.json", "broken.schema.json"] {
+        let error = JsonSchemaParser::from_path(&dir.join(name)).err().unwrap();
+        assert_eq!(std::io::ErrorKind::InvalidData, error.kind());
+        assert!(error.to_string().contains("broken.schema.json"));
+    }
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn collect_refs_tests() {
+    assert_eq!(
+        vec![
+            "glTFChildOfRootProperty.schema.json",
+            "glTFid.schema.json",
+            "#/definitions/weight",
+        ],
+        JsonSchemaParser::collect_refs(
+            r##"{
+    "allOf": [ { "$ref": "glTFChildOfRootProperty.schema.json" } ],
+    "properties": {
+        "mesh": { "allOf": [ { "$ref": "glTFid.schema.json" } ] },
+        "weights": { "type": "array", "items": { "$ref": "#/definitions/weight" } },
+        "name": { "type": "string" }
+    }
+}"##
+        )
+        .unwrap()
+    );
+    assert!(JsonSchemaParser::collect_refs("{}").unwrap().is_empty());
+    assert!(JsonSchemaParser::collect_refs("{").is_err());
+}
+
+#[test]
+fn load_order_tests() {
+    use std::collections::HashMap;
+
+    let files: HashMap<PathBuf, &str> = vec![
+        (
+            PathBuf::from("schema/node.schema.json"),
+            r##"{ "allOf": [ { "$ref": "glTFChildOfRootProperty.schema.json" } ], "properties": { "mesh": { "$ref": "glTFid.schema.json" } } }"##,
+        ),
+        (
+            PathBuf::from("schema/glTFChildOfRootProperty.schema.json"),
+            r##"{ "allOf": [ { "$ref": "glTFProperty.schema.json" } ] }"##,
+        ),
+        (
+            PathBuf::from("schema/glTFProperty.schema.json"),
+            r##"{ "properties": { "extras": { "$ref": "#/definitions/extras" } } }"##,
+        ),
+        (
+            PathBuf::from("schema/broken.schema.json"),
+            r##"{ "$ref": "glTFid.schema.json" "##,
+        ),
+        (
+            PathBuf::from("schema/glTFid.schema.json"),
+            r##"{ "type": "integer" }"##,
+        ),
+        (
+            PathBuf::from("schema/a.schema.json"),
+            r##"{ "$ref": "b.schema.json" }"##,
+        ),
+        (
+            PathBuf::from("schema/b.schema.json"),
+            r##"{ "$ref": "a.schema.json#/definitions/x" }"##,
+        ),
+    ]
+    .into_iter()
+    .collect();
+    let loader = |path: &Path| {
+        files
+            .get(path)
+            .map(|src| src.to_string())
+            .ok_or_else(|| std::io::Error::from(std::io::ErrorKind::NotFound))
+    };
+
+    assert_eq!(
+        vec![
+            PathBuf::from("schema/glTFProperty.schema.json"),
+            PathBuf::from("schema/glTFChildOfRootProperty.schema.json"),
+            PathBuf::from("schema/glTFid.schema.json"),
+            PathBuf::from("schema/node.schema.json"),
+        ],
+        JsonSchemaParser::load_order(Path::new("schema/node.schema.json"), loader).unwrap()
+    );
+
+    match JsonSchemaParser::load_order(Path::new("schema/a.schema.json"), loader) {
+        Err(LoadOrderError::Cycle(cycle)) => assert_eq!(
+            vec![
+                PathBuf::from("schema/a.schema.json"),
+                PathBuf::from("schema/b.schema.json"),
+                PathBuf::from("schema/a.schema.json"),
+            ],
+            cycle
+        ),
+        _ => panic!(),
+    }
+
+    assert!(matches!(
+        JsonSchemaParser::load_order(Path::new("schema/none.schema.json"), loader),
+        Err(LoadOrderError::Io(_, _))
+    ));
+
+    match JsonSchemaParser::load_order(Path::new("schema/broken.schema.json"), loader) {
+        Err(LoadOrderError::Parse(path, ParseError::Eof())) => {
+            assert_eq!(PathBuf::from("schema/broken.schema.json"), path)
+        }
+        _ => panic!(),
+    }
+}
+
+#[test]
+fn unknown_keywords_tests() {
+    let parser = JsonSchemaParser::from_str(
+        r##"{
+    "type": "object",
+    "minProperties": 1,
+    "properties": { "count": { "type": "integer", "minimum": 0, "enum": [0, 1] } }
+}"##,
+    );
+    let keywords = |schema: &JsonSchema| {
+        schema
+            .unknown_keywords
+            .iter()
+            .map(|(k, v)| format!("{} => {}", k, v))
+            .collect::<Vec<String>>()
+    };
+    assert_eq!(vec!["minProperties => 1"], keywords(&parser.root));
+    assert_eq!(
+        vec!["minimum => 0", "enum => [0,1]"],
+        keywords(parser.root.property("count").unwrap())
+    );
 }
@@ -0,0 +1,995 @@
+use super::super::node::JsonNode;
+use super::super::parser::JsonParser;
+use super::super::parser::JsonValue;
+use super::super::parser::Number;
+use super::super::string::{pointer_token, unescape};
+use super::super::value::OwnedValue;
+use super::parser::{JsonSchema, JsonSchemaParser};
+use alloc::borrow::Cow;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    pub pointer: String, // json pointer of the instance. "" is root
+    pub message: String,
+}
+
+// a subschema that applied to an instance location without errors
+#[derive(Debug, Clone, PartialEq)]
+pub struct Annotation {
+    pub pointer: String, // json pointer of the instance
+    pub keyword: &'static str,
+    // json pointer in the schema document. "$ref" is followed as "/$ref"
+    pub schema_pointer: String,
+}
+
+impl core::fmt::Display for Annotation {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{} {} by schema at {}",
+            self.pointer, self.keyword, self.schema_pointer
+        )
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ValidationOutput {
+    pub errors: Vec<ValidationError>,
+    pub annotations: Vec<Annotation>,
+}
+
+impl ValidationOutput {
+    // records keyword when f added no errors
+    fn annotate(
+        &mut self,
+        annotate: bool,
+        pointer: &str,
+        keyword: &'static str,
+        schema_pointer: String,
+        f: impl FnOnce(&mut ValidationOutput),
+    ) {
+        let errors = self.errors.len();
+        f(self);
+        if annotate && self.errors.len() == errors {
+            self.annotations.push(Annotation {
+                pointer: pointer.to_string(),
+                keyword,
+                schema_pointer,
+            });
+        }
+    }
+}
+
+fn type_matches(json_type: &str, node: &JsonNode) -> bool {
+    match (json_type, node.value()) {
+        ("null", JsonValue::Null()) => true,
+        ("boolean", JsonValue::True()) | ("boolean", JsonValue::False()) => true,
+        ("number", JsonValue::Number(..)) => true,
+        ("integer", JsonValue::Number(_, true)) => true,
+        // 1.0 is an integer
+        ("integer", JsonValue::Number(_, false)) => {
+            node.get_f64().is_some_and(|n| n.fract() == 0.0)
+        }
+        ("string", JsonValue::String(_)) => true,
+        ("array", JsonValue::ArrayOpen(_)) => true,
+        ("object", JsonValue::ObjectOpen(_)) => true,
+        _ => false,
+    }
+}
+
+#[cfg(feature = "regex")]
+fn pattern_matches(pattern: &str, text: &str) -> Result<bool, String> {
+    match regex::Regex::new(pattern) {
+        Ok(re) => Ok(re.is_match(text)),
+        Err(_) => Err(format!("pattern {} is invalid", pattern)),
+    }
+}
+
+// "5" => 5 for "integer" and "number", "true" => true for "boolean"
+fn coerce_string(json_type: &str, text: &str) -> Option<OwnedValue> {
+    match json_type {
+        "boolean" => match text {
+            "true" => Some(OwnedValue::Bool(true)),
+            "false" => Some(OwnedValue::Bool(false)),
+            _ => None,
+        },
+        "integer" | "number" => {
+            let parser = JsonParser::try_process_strict(text).ok()?;
+            match parser.get_number(0)? {
+                // 1.0 is an integer, as in type_matches
+                Number::Float(n) if json_type == "integer" && n.fract() != 0.0 => None,
+                number => Some(OwnedValue::Number(number)),
+            }
+        }
+        _ => None,
+    }
+}
+
+impl JsonSchema {
+    // copy of node with strings converted to the declared boolean or number type,
+    // and the errors left in the copy
+    pub fn validate_coerce(&self, node: &JsonNode) -> (OwnedValue, Vec<ValidationError>) {
+        let mut value = OwnedValue::from_node(node);
+        self.coerce_to(&mut value);
+        let src = value.to_string();
+        let parser = JsonParser::process(&src);
+        let errors = self.validate(&JsonNode::new(&parser));
+        (value, errors)
+    }
+
+    fn coerce_to(&self, value: &mut OwnedValue) {
+        if let Some(ref_schema) = &self.ref_schema {
+            ref_schema.coerce_to(value);
+        }
+        for branch in &self.all_of {
+            branch.coerce_to(value);
+        }
+        if let (Some(json_type), OwnedValue::String(text)) = (&self.json_type, &*value) {
+            if let Some(coerced) = coerce_string(json_type, text) {
+                *value = coerced;
+            }
+        }
+        match value {
+            OwnedValue::Object(members) => {
+                for (key, member) in members {
+                    let mut matched = false;
+                    if let Some(property) = self.property(key) {
+                        matched = true;
+                        property.coerce_to(member);
+                    }
+                    // every matching pattern applies
+                    #[cfg(feature = "regex")]
+                    for (pattern, property) in &self.pattern_properties {
+                        if pattern_matches(pattern, key) == Ok(true) {
+                            matched = true;
+                            property.coerce_to(member);
+                        }
+                    }
+                    #[cfg(not(feature = "regex"))]
+                    if !self.pattern_properties.is_empty() {
+                        matched = true;
+                    }
+                    if !matched {
+                        if let Some(additional_properties) = &self.additional_properties {
+                            additional_properties.coerce_to(member);
+                        }
+                    }
+                }
+            }
+            OwnedValue::Array(items) => {
+                for (i, item) in items.iter_mut().enumerate() {
+                    let schema = match self.prefix_items.get(i) {
+                        Some(prefix) => Some(prefix),
+                        None => self.items.as_deref(),
+                    };
+                    if let Some(schema) = schema {
+                        schema.coerce_to(item);
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+
+    pub fn validate(&self, node: &JsonNode) -> Vec<ValidationError> {
+        let mut out = ValidationOutput::default();
+        self.validate_to("", "", node, &mut out, false);
+        out.errors
+    }
+
+    // errors and the properties and items that were validated.
+    // absent properties with a default are annotated with "default"
+    pub fn validate_with_annotations(&self, node: &JsonNode) -> ValidationOutput {
+        let mut out = ValidationOutput::default();
+        self.validate_to("", "", node, &mut out, true);
+        out
+    }
+
+    fn validate_to(
+        &self,
+        pointer: &str,
+        schema_pointer: &str,
+        node: &JsonNode,
+        out: &mut ValidationOutput,
+        annotate: bool,
+    ) {
+        let mut error = |message: String| {
+            out.errors.push(ValidationError {
+                pointer: pointer.to_string(),
+                message,
+            })
+        };
+
+        if self.is_false {
+            error(format!("{} is not allowed", node));
+            return;
+        }
+
+        if let Some(json_type) = &self.json_type {
+            if !type_matches(json_type, node) {
+                error(format!("{} is not {}", node, json_type));
+                return;
+            }
+        }
+
+        for name in &self.required {
+            if node.object_len().is_some() && node.key(name).is_err() {
+                error(format!("required property {} is missing", name));
+            }
+        }
+
+        #[cfg(feature = "regex")]
+        if let Some(pattern) = &self.pattern {
+            if let Some(text) = node.get_str() {
+                match pattern_matches(pattern, &text) {
+                    Ok(true) => (),
+                    Ok(false) => error(format!("{} does not match {}", node, pattern)),
+                    Err(message) => error(message),
+                }
+            }
+        }
+
+        if annotate && node.object_len().is_some() {
+            for (name, property) in &self.properties {
+                if property.default_value.is_some() && node.key(name).is_err() {
+                    out.annotations.push(Annotation {
+                        pointer: format!("{}/{}", pointer, pointer_token(name)),
+                        keyword: "default",
+                        schema_pointer: format!(
+                            "{}/properties/{}/default",
+                            schema_pointer,
+                            pointer_token(name)
+                        ),
+                    });
+                }
+            }
+        }
+
+        if let Some(ref_schema) = &self.ref_schema {
+            let schema_pointer = format!("{}/$ref", schema_pointer);
+            ref_schema.validate_to(pointer, &schema_pointer, node, out, annotate);
+        }
+
+        // each branch sees the whole instance, so required in one branch
+        // holds for a property defined in another
+        for (i, branch) in self.all_of.iter().enumerate() {
+            let schema_pointer = format!("{}/allOf/{}", schema_pointer, i);
+            branch.validate_to(pointer, &schema_pointer, node, out, annotate);
+        }
+
+        for (key, value) in node.object_iter() {
+            // "a\/b" is checked as "a/b"
+            let name = unescape(key).unwrap_or(Cow::Borrowed(key));
+            // glTF extras is application data. anything goes unless the schema says otherwise
+            if name == "extras" && self.property(&name).is_none() {
+                continue;
+            }
+            if let Some(property_names) = &self.property_names {
+                // the name as a string instance
+                let src = OwnedValue::String(name.to_string()).to_string();
+                if let Ok(instance) = JsonParser::try_process(&src) {
+                    for e in property_names.validate(&JsonNode::new(&instance)) {
+                        out.errors.push(ValidationError {
+                            pointer: format!("{}/{}", pointer, pointer_token(key)),
+                            message: format!("property name {}", e.message),
+                        });
+                    }
+                }
+            }
+            let member = format!("{}/{}", pointer, pointer_token(key));
+            let mut matched = false;
+            if let Some(property) = self.property(&name) {
+                matched = true;
+                let at = format!("{}/properties/{}", schema_pointer, pointer_token(key));
+                out.annotate(annotate, &member, "properties", at.clone(), |out| {
+                    property.validate_to(&member, &at, &value, out, annotate)
+                });
+            }
+            // every matching pattern applies
+            #[cfg(feature = "regex")]
+            for (pattern, property) in &self.pattern_properties {
+                match pattern_matches(pattern, &name) {
+                    Ok(true) => {
+                        matched = true;
+                        let at = format!(
+                            "{}/patternProperties/{}",
+                            schema_pointer,
+                            pointer_token(pattern)
+                        );
+                        out.annotate(annotate, &member, "patternProperties", at.clone(), |out| {
+                            property.validate_to(&member, &at, &value, out, annotate)
+                        });
+                    }
+                    Ok(false) => (),
+                    Err(message) => out.errors.push(ValidationError {
+                        pointer: member.clone(),
+                        message,
+                    }),
+                }
+            }
+            // unknown without regex. additionalProperties is not checked
+            #[cfg(not(feature = "regex"))]
+            if !self.pattern_properties.is_empty() {
+                matched = true;
+            }
+            if !matched {
+                if let Some(additional_properties) = &self.additional_properties {
+                    let at = format!("{}/additionalProperties", schema_pointer);
+                    out.annotate(
+                        annotate,
+                        &member,
+                        "additionalProperties",
+                        at.clone(),
+                        |out| {
+                            additional_properties.validate_to(&member, &at, &value, out, annotate)
+                        },
+                    );
+                }
+            }
+        }
+
+        // items: false closes the tuple. one error for the length instead of one per extra element
+        let mut len = node.array_len().unwrap_or(0);
+        if self.items.as_deref().is_some_and(|items| items.is_false)
+            && len > self.prefix_items.len()
+        {
+            out.errors.push(ValidationError {
+                pointer: pointer.to_string(),
+                message: format!(
+                    "{} items, expected at most {}",
+                    len,
+                    self.prefix_items.len()
+                ),
+            });
+            len = self.prefix_items.len();
+        }
+        for (i, value) in node.array_iter().take(len).enumerate() {
+            let (item, keyword, at) = match self.prefix_items.get(i) {
+                Some(prefix) => (
+                    Some(prefix),
+                    "prefixItems",
+                    format!("{}/prefixItems/{}", schema_pointer, i),
+                ),
+                None => (
+                    self.items.as_deref(),
+                    "items",
+                    format!("{}/items", schema_pointer),
+                ),
+            };
+            if let Some(item) = item {
+                let element = format!("{}/{}", pointer, i);
+                out.annotate(annotate, &element, keyword, at.clone(), |out| {
+                    item.validate_to(&element, &at, &value, out, annotate)
+                });
+            }
+        }
+
+        if let Some(contains) = &self.contains {
+            if node.array_len().is_some() {
+                let count = node
+                    .array_iter()
+                    .filter(|value| contains.validate(value).is_empty())
+                    .count();
+                let min = self.min_contains.unwrap_or(1);
+                if count < min {
+                    out.errors.push(ValidationError {
+                        pointer: pointer.to_string(),
+                        message: format!(
+                            "{} elements match contains, expected at least {}",
+                            count, min
+                        ),
+                    });
+                }
+                if let Some(max) = self.max_contains {
+                    if count > max {
+                        out.errors.push(ValidationError {
+                            pointer: pointer.to_string(),
+                            message: format!(
+                                "{} elements match contains, expected at most {}",
+                                count, max
+                            ),
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl JsonSchemaParser {
+    // root schema, then each member of every "extensions" object against the registered
+    // extension schema. unknown extensions are errors only if strict
+    pub fn validate(&self, node: &JsonNode, strict: bool) -> Vec<ValidationError> {
+        let mut errors = self.root.validate(node);
+        self.validate_extensions_to("", node, strict, &mut errors);
+        errors
+    }
+
+    fn validate_extensions_to(
+        &self,
+        pointer: &str,
+        node: &JsonNode,
+        strict: bool,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        for (key, value) in node.object_iter() {
+            let member = format!("{}/{}", pointer, pointer_token(key));
+            if key == "extensions" {
+                for (name, extension) in value.object_iter() {
+                    let member = format!("{}/{}", member, pointer_token(name));
+                    match self.extension(name) {
+                        Some(schema) => {
+                            let mut out = ValidationOutput::default();
+                            schema.validate_to(&member, "", &extension, &mut out, false);
+                            errors.append(&mut out.errors);
+                        }
+                        None if strict => errors.push(ValidationError {
+                            pointer: member.clone(),
+                            message: format!("extension {} is unknown", name),
+                        }),
+                        None => (),
+                    }
+                    self.validate_extensions_to(&member, &extension, strict, errors);
+                }
+            } else if key != "extras" {
+                self.validate_extensions_to(&member, &value, strict, errors);
+            }
+        }
+        for (i, value) in node.array_iter().enumerate() {
+            self.validate_extensions_to(&format!("{}/{}", pointer, i), &value, strict, errors);
+        }
+    }
+}
+
+#[test]
+fn validate_tests() {
+    use super::parser::JsonSchemaParser;
+
+    let schema = JsonSchemaParser::from_str(
+        r##"{
+    "type": "object",
+    "properties": {
+        "name": { "type": "string" },
+        "count": { "type": "integer" },
+        "children": { "type": "array", "items": { "type": "integer" } }
+    },
+    "required": [ "name" ]
+}"##,
+    )
+    .root;
+
+    let validate = |src: &str| schema.validate(&JsonNode::new(&JsonParser::process(src)));
+
+    assert!(validate(r##"{"name": "a", "count": 1.0, "children": [1, 2]}"##).is_empty());
+    assert_eq!(
+        vec![ValidationError {
+            pointer: String::new(),
+            message: "required property name is missing".to_string(),
+        }],
+        validate("{}")
+    );
+    assert_eq!(
+        vec![
+            ValidationError {
+                pointer: "/count".to_string(),
+                message: "1.5 is not integer".to_string(),
+            },
+            ValidationError {
+                pointer: "/children/1".to_string(),
+                message: r##""x" is not integer"##.to_string(),
+            },
+        ],
+        validate(r##"{"name": "a", "count": 1.5, "children": [1, "x"]}"##)
+    );
+    assert_eq!(1, validate("[]").len());
+}
+
+#[test]
+fn draft_tests() {
+    use super::parser::{JsonSchemaParser, SchemaDraft};
+
+    let src = r##"{ "type": "array", "prefixItems": [ { "type": "number" } ] }"##;
+    let instance = JsonParser::process(r##"["x"]"##);
+
+    // ignored in draft-07
+    let schema = JsonSchemaParser::from_str(src).root;
+    assert_eq!(SchemaDraft::Draft7, schema.draft);
+    assert!(schema.prefix_items.is_empty());
+    assert!(schema.validate(&JsonNode::new(&instance)).is_empty());
+
+    let schema = JsonSchemaParser::from_str_draft(src, SchemaDraft::Draft2020_12).root;
+    assert_eq!(1, schema.validate(&JsonNode::new(&instance)).len());
+
+    // detected from $schema
+    let schema = JsonSchemaParser::from_str(
+        r##"{
+    "$schema": "https://json-schema.org/draft/2020-12/schema",
+    "type": "array",
+    "prefixItems": [ { "type": "number" } ]
+}"##,
+    )
+    .root;
+    assert_eq!(SchemaDraft::Draft2020_12, schema.draft);
+    assert_eq!(
+        vec![ValidationError {
+            pointer: "/0".to_string(),
+            message: r##""x" is not number"##.to_string(),
+        }],
+        schema.validate(&JsonNode::new(&instance))
+    );
+}
+
+#[test]
+fn prefix_items_tests() {
+    use super::parser::{JsonSchemaParser, SchemaDraft};
+
+    let schema = JsonSchemaParser::from_str_draft(
+        r##"{
+    "type": "array",
+    "prefixItems": [ { "type": "number" }, { "type": "string" } ],
+    "items": { "type": "boolean" }
+}"##,
+        SchemaDraft::Draft2020_12,
+    )
+    .root;
+    let validate = |src: &str| schema.validate(&JsonNode::new(&JsonParser::process(src)));
+
+    assert!(validate(r##"[1.5, "a"]"##).is_empty());
+    assert!(validate(r##"[1.5, "a", true, false]"##).is_empty());
+    assert_eq!(
+        vec![ValidationError {
+            pointer: "/1".to_string(),
+            message: "2 is not string".to_string(),
+        }],
+        validate("[1, 2]")
+    );
+    assert_eq!(
+        vec![ValidationError {
+            pointer: "/2".to_string(),
+            message: "3 is not boolean".to_string(),
+        }],
+        validate(r##"[1, "a", 3]"##)
+    );
+
+    // draft-07 array form
+    let schema = JsonSchemaParser::from_str(
+        r##"{
+    "type": "array",
+    "items": [ { "type": "number" }, { "type": "string" } ],
+    "additionalItems": { "type": "boolean" }
+}"##,
+    )
+    .root;
+    assert_eq!(2, schema.prefix_items.len());
+    let instance = JsonParser::process(r##"[1, 2, 3]"##);
+    assert_eq!(2, schema.validate(&JsonNode::new(&instance)).len());
+}
+
+#[test]
+fn items_false_tests() {
+    use super::parser::{JsonSchemaParser, SchemaDraft};
+
+    let schema = JsonSchemaParser::from_str_draft(
+        r##"{
+    "type": "array",
+    "prefixItems": [ { "type": "number" }, { "type": "number" } ],
+    "items": false
+}"##,
+        SchemaDraft::Draft2020_12,
+    )
+    .root;
+    let validate = |src: &str| schema.validate(&JsonNode::new(&JsonParser::process(src)));
+    assert!(validate("[1, 2]").is_empty());
+    assert!(validate("[1]").is_empty());
+    assert_eq!(
+        vec![ValidationError {
+            pointer: String::new(),
+            message: "4 items, expected at most 2".to_string(),
+        }],
+        validate("[1, 2, 3, 4]")
+    );
+
+    // draft-07
+    let schema = JsonSchemaParser::from_str(
+        r##"{ "items": [ { "type": "number" } ], "additionalItems": false }"##,
+    )
+    .root;
+    let instance = JsonParser::process(r##"["a", 2]"##);
+    assert_eq!(
+        vec![
+            ValidationError {
+                pointer: String::new(),
+                message: "2 items, expected at most 1".to_string(),
+            },
+            ValidationError {
+                pointer: "/0".to_string(),
+                message: "\"a\" is not number".to_string(),
+            },
+        ],
+        schema.validate(&JsonNode::new(&instance))
+    );
+}
+
+#[test]
+fn all_of_required_tests() {
+    use super::parser::JsonSchemaParser;
+
+    let schema = JsonSchemaParser::from_str(
+        r##"{
+    "type": "object",
+    "allOf": [
+        { "properties": { "uri": { "type": "string" }, "byteLength": { "type": "integer" } } },
+        { "required": [ "byteLength" ] }
+    ]
+}"##,
+    )
+    .root;
+    assert_eq!(2, schema.all_of.len());
+    let validate = |src: &str| schema.validate(&JsonNode::new(&JsonParser::process(src)));
+
+    assert!(validate(r##"{ "byteLength": 4 }"##).is_empty());
+    assert_eq!(
+        vec![ValidationError {
+            pointer: String::new(),
+            message: "required property byteLength is missing".to_string(),
+        }],
+        validate(r##"{ "uri": "a.bin" }"##)
+    );
+    assert_eq!(
+        vec![ValidationError {
+            pointer: "/byteLength".to_string(),
+            message: "\"4\" is not integer".to_string(),
+        }],
+        validate(r##"{ "byteLength": "4" }"##)
+    );
+}
+
+#[test]
+fn contains_tests() {
+    use super::parser::JsonSchemaParser;
+
+    let schema =
+        JsonSchemaParser::from_str(r##"{ "type": "array", "contains": { "type": "string" } }"##)
+            .root;
+    let validate = |src: &str| schema.validate(&JsonNode::new(&JsonParser::process(src)));
+
+    assert!(validate(r##"[1, "a", 2]"##).is_empty());
+    assert_eq!(
+        vec![ValidationError {
+            pointer: String::new(),
+            message: "0 elements match contains, expected at least 1".to_string(),
+        }],
+        validate("[1, 2]")
+    );
+
+    let schema = JsonSchemaParser::from_str(
+        r##"{ "contains": { "type": "integer" }, "minContains": 2, "maxContains": 3 }"##,
+    )
+    .root;
+    let validate = |src: &str| schema.validate(&JsonNode::new(&JsonParser::process(src)));
+    assert_eq!(1, validate("[1]").len());
+    assert!(validate("[1, 2, 3]").is_empty());
+    assert_eq!(1, validate("[1, 2, 3, 4]").len());
+
+    // negative counts are ignored instead of wrapping around
+    let schema = JsonSchemaParser::from_str(
+        r##"{ "contains": { "type": "integer" }, "minContains": -1, "maxContains": -1 }"##,
+    )
+    .root;
+    assert_eq!(None, schema.min_contains);
+    assert_eq!(None, schema.max_contains);
+    let validate = |src: &str| schema.validate(&JsonNode::new(&JsonParser::process(src)));
+    assert!(validate("[1, 2]").is_empty());
+    assert_eq!(1, validate(r##"["a"]"##).len());
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn property_names_tests() {
+    use super::parser::JsonSchemaParser;
+
+    let schema = JsonSchemaParser::from_str(
+        r##"{ "type": "object", "propertyNames": { "pattern": "^[A-Za-z_][A-Za-z0-9_]*$" } }"##,
+    )
+    .root;
+    let validate = |src: &str| schema.validate(&JsonNode::new(&JsonParser::process(src)));
+
+    assert!(validate(r##"{"name": 1, "_id": 2}"##).is_empty());
+    assert_eq!(
+        vec![ValidationError {
+            pointer: "/a b".to_string(),
+            message: r##"property name "a b" does not match ^[A-Za-z_][A-Za-z0-9_]*$"##.to_string(),
+        }],
+        validate(r##"{"name": 1, "a b": 2}"##)
+    );
+    // the unescaped name is checked
+    assert!(validate(r##"{"\u0041bc": 1, "\u005fid": 2}"##).is_empty());
+}
+
+#[cfg(feature = "regex")]
+#[test]
+fn pattern_properties_tests() {
+    use super::parser::JsonSchemaParser;
+
+    let schema = JsonSchemaParser::from_str(
+        r##"{
+    "type": "object",
+    "properties": { "name": { "type": "string" } },
+    "patternProperties": {
+        "^x-": { "type": "integer" },
+        "-id$": { "type": "string" }
+    },
+    "additionalProperties": false
+}"##,
+    )
+    .root;
+    let validate = |src: &str| schema.validate(&JsonNode::new(&JsonParser::process(src)));
+
+    assert!(validate(r##"{"name": "a", "x-count": 1, "y-id": "b"}"##).is_empty());
+    // patterns see the unescaped name
+    assert!(validate(r##"{"\u0078-count": 1, "y-\u0069d": "b"}"##).is_empty());
+    assert_eq!(
+        vec![ValidationError {
+            pointer: "/x-count".to_string(),
+            message: r##""1" is not integer"##.to_string(),
+        }],
+        validate(r##"{"x-count": "1"}"##)
+    );
+    // both patterns apply
+    assert_eq!(
+        vec![ValidationError {
+            pointer: "/x-id".to_string(),
+            message: "1 is not string".to_string(),
+        }],
+        validate(r##"{"x-id": 1}"##)
+    );
+    assert_eq!(
+        vec![ValidationError {
+            pointer: "/other".to_string(),
+            message: "1 is not allowed".to_string(),
+        }],
+        validate(r##"{"other": 1}"##)
+    );
+}
+
+#[test]
+fn validate_extensions_tests() {
+    let mut parser = JsonSchemaParser::from_str(r##"{ "type": "object" }"##);
+    let lights = JsonSchemaParser::from_str(
+        r##"{
+    "type": "object",
+    "properties": { "intensity": { "type": "number" } },
+    "required": [ "intensity" ]
+}"##,
+    )
+    .root;
+    parser.register_extension("KHR_lights", lights);
+
+    let src = r##"{
+    "nodes": [
+        { "extensions": { "KHR_lights": { "intensity": "x" }, "VENDOR_unknown": {} } }
+    ]
+}"##;
+    let instance = JsonParser::process(src);
+    let node = JsonNode::new(&instance);
+    assert_eq!(
+        vec![ValidationError {
+            pointer: "/nodes/0/extensions/KHR_lights/intensity".to_string(),
+            message: r##""x" is not number"##.to_string(),
+        }],
+        parser.validate(&node, false)
+    );
+    let errors = parser.validate(&node, true);
+    assert_eq!(2, errors.len());
+    assert_eq!("/nodes/0/extensions/VENDOR_unknown", errors[1].pointer);
+    assert_eq!("extension VENDOR_unknown is unknown", errors[1].message);
+}
+
+#[test]
+fn extras_tests() {
+    let parser = JsonSchemaParser::from_str(
+        r##"{
+    "type": "object",
+    "properties": {
+        "name": { "type": "string" },
+        "extras": { "type": "object" }
+    },
+    "additionalProperties": false
+}"##,
+    );
+    let validate = |src: &str| parser.validate(&JsonNode::new(&JsonParser::process(src)), true);
+    assert!(
+        validate(r##"{"name": "a", "extras": {"a": [1, {"extensions": {"x": 1}}]}}"##).is_empty()
+    );
+    // declared extras is validated like any other property
+    assert_eq!(1, validate(r##"{"extras": "text"}"##).len());
+    assert_eq!(2, validate(r##"{"name": 1, "extras": null}"##).len());
+
+    // undeclared extras is accepted even without additionalProperties
+    let parser = JsonSchemaParser::from_str(
+        r##"{ "type": "object", "properties": { "name": { "type": "string" } }, "additionalProperties": false }"##,
+    );
+    let validate = |src: &str| parser.validate(&JsonNode::new(&JsonParser::process(src)), true);
+    assert!(validate(r##"{"name": "a", "extras": [1, {"extensions": {"x": 1}}]}"##).is_empty());
+    assert!(validate(r##"{"extras": "text"}"##).is_empty());
+    assert!(validate(r##"{"extras": null}"##).is_empty());
+    assert_eq!(1, validate(r##"{"other": null}"##).len());
+}
+
+#[test]
+fn validate_with_annotations_tests() {
+    let parser = JsonSchemaParser::from_str(
+        r##"{
+    "type": "object",
+    "properties": {
+        "name": { "type": "string" },
+        "scale": { "type": "number", "default": 1 },
+        "children": { "type": "array", "items": { "type": "integer" } }
+    }
+}"##,
+    );
+    let instance = JsonParser::process(r##"{"name": "a", "children": [1, "x"]}"##);
+    let out = parser
+        .root
+        .validate_with_annotations(&JsonNode::new(&instance));
+    assert_eq!(1, out.errors.len());
+    assert_eq!(
+        vec![
+            "/scale default by schema at /properties/scale/default",
+            "/name properties by schema at /properties/name",
+            "/children/0 items by schema at /properties/children/items",
+        ],
+        out.annotations
+            .iter()
+            .map(|a| a.to_string())
+            .collect::<Vec<_>>()
+    );
+    assert_eq!(out.errors, parser.root.validate(&JsonNode::new(&instance)));
+}
+
+#[test]
+fn validate_coerce_tests() {
+    let parser = JsonSchemaParser::from_str(
+        r##"{
+    "type": "object",
+    "properties": {
+        "count": { "type": "integer" },
+        "scale": { "type": "number" },
+        "visible": { "type": "boolean" },
+        "name": { "type": "string" },
+        "indices": { "type": "array", "items": { "type": "integer" } }
+    }
+}"##,
+    );
+    let coerce = |src: &str| {
+        let instance = JsonParser::process(src);
+        let (value, errors) = parser.root.validate_coerce(&JsonNode::new(&instance));
+        (value.to_string(), errors.len())
+    };
+
+    assert_eq!(
+        (
+            r##"{"count":5,"scale":0.5,"visible":true,"name":"7","indices":[1,2]}"##.to_string(),
+            0
+        ),
+        coerce(
+            r##"{"count": "5", "scale": "0.5", "visible": "true", "name": "7", "indices": ["1", 2]}"##
+        )
+    );
+    // not coercible, left as is
+    assert_eq!(
+        (
+            r##"{"count":"1.5","visible":"yes","scale":"x"}"##.to_string(),
+            3
+        ),
+        coerce(r##"{"count": "1.5", "visible": "yes", "scale": "x"}"##)
+    );
+}
+
+#[test]
+fn coerce_subschema_tests() {
+    let coerce = |schema: &str, src: &str| {
+        let parser = JsonSchemaParser::from_str(schema);
+        let instance = JsonParser::process(src);
+        let (value, errors) = parser.root.validate_coerce(&JsonNode::new(&instance));
+        (value.to_string(), errors.len())
+    };
+
+    assert_eq!(
+        (r##"{"count":5}"##.to_string(), 0),
+        coerce(
+            r##"{"allOf": [{"properties": {"count": {"type": "integer"}}}]}"##,
+            r##"{"count": "5"}"##
+        )
+    );
+    // 1.0 is an integer for coercion as well as validation
+    assert_eq!(
+        (r##"{"count":1.0}"##.to_string(), 0),
+        coerce(
+            r##"{"properties": {"count": {"type": "integer"}}}"##,
+            r##"{"count": "1.0"}"##
+        )
+    );
+    #[cfg(feature = "regex")]
+    assert_eq!(
+        (r##"{"x_1":1,"x_2":true,"y":"2"}"##.to_string(), 0),
+        coerce(
+            r##"{"patternProperties": {"1$": {"type": "integer"}, "2$": {"type": "boolean"}},
+                "additionalProperties": {"type": "string"}}"##,
+            r##"{"x_1": "1", "x_2": "true", "y": "2"}"##
+        )
+    );
+}
+
+#[test]
+fn escaped_key_tests() {
+    let parser = JsonSchemaParser::from_str(
+        r##"{ "properties": { "a/b": { "type": "integer" }, "c\/d": { "type": "string" } }, "additionalProperties": false }"##,
+    );
+    let validate = |src: &str| {
+        parser
+            .root
+            .validate(&JsonNode::new(&JsonParser::process(src)))
+    };
+
+    // schema and instance keys match after unescape
+    assert!(validate(r##"{"a\/b": 1, "c/d": "x"}"##).is_empty());
+    assert_eq!(
+        vec![r##""x" is not integer"##],
+        validate(r##"{"a\/b": "x"}"##)
+            .iter()
+            .map(|e| e.message.as_str())
+            .collect::<Vec<_>>()
+    );
+
+    // "\\d" in the schema document is the regex \d
+    #[cfg(feature = "regex")]
+    {
+        let parser = JsonSchemaParser::from_str(
+            r##"{ "patternProperties": { "^\\d+$": { "type": "integer" } }, "additionalProperties": false }"##,
+        );
+        let validate = |src: &str| {
+            parser
+                .root
+                .validate(&JsonNode::new(&JsonParser::process(src)))
+        };
+        assert!(validate(r##"{"12": 1}"##).is_empty());
+        assert_eq!(1, validate(r##"{"12": "x"}"##).len());
+    }
+}
+
+#[test]
+fn pointer_escape_tests() {
+    let parser = JsonSchemaParser::from_str(
+        r##"{
+    "properties": { "a/b": { "type": "integer" } },
+    "additionalProperties": false
+}"##,
+    );
+    let validate = |src: &str| {
+        parser
+            .root
+            .validate(&JsonNode::new(&JsonParser::process(src)))
+    };
+
+    // keys are unescaped, then written as json pointer tokens
+    assert_eq!(
+        vec![
+            ValidationError {
+                pointer: "/a~1b".to_string(),
+                message: r##""x" is not integer"##.to_string(),
+            },
+            ValidationError {
+                pointer: "/m~0n".to_string(),
+                message: "1 is not allowed".to_string(),
+            },
+        ],
+        validate(r##"{"a\/b": "x", "m~n": 1}"##)
+    );
+
+    let output = parser
+        .root
+        .validate_with_annotations(&JsonNode::new(&JsonParser::process(r##"{"a/b": 1}"##)));
+    assert_eq!("/a~1b", output.annotations[0].pointer);
+    assert_eq!("/properties/a~1b", output.annotations[0].schema_pointer);
+}
@@ -0,0 +1,55 @@
+use super::node::JsonNode;
+use super::parser::{JsonValue, Number};
+use super::string::unescape;
+use serde::ser::{Error, Serialize, SerializeMap, SerializeSeq, Serializer};
+
+impl<'a> Serialize for JsonNode<'a> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self.value() {
+            JsonValue::Null() => serializer.serialize_unit(),
+            JsonValue::True() => serializer.serialize_bool(true),
+            JsonValue::False() => serializer.serialize_bool(false),
+            JsonValue::Number(..) => match self.as_number() {
+                Some(Number::Int(n)) => serializer.serialize_i64(n),
+                Some(Number::UInt(n)) => serializer.serialize_u64(n),
+                Some(Number::Float(n)) => serializer.serialize_f64(n),
+                None => Err(S::Error::custom("invalid number")),
+            },
+            JsonValue::String(_) => match self.get_str() {
+                Some(s) => serializer.serialize_str(&s),
+                None => Err(S::Error::custom("invalid escape")),
+            },
+            JsonValue::ArrayOpen(_) => {
+                let mut seq = serializer.serialize_seq(self.array_len())?;
+                for child in self.array_iter() {
+                    seq.serialize_element(&child)?;
+                }
+                seq.end()
+            }
+            JsonValue::ObjectOpen(_) => {
+                let mut map = serializer.serialize_map(self.object_len())?;
+                for (key, child) in self.object_iter() {
+                    match unescape(key) {
+                        Some(key) => map.serialize_entry(&*key, &child)?,
+                        None => return Err(S::Error::custom("invalid escape")),
+                    }
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+#[test]
+fn serialize_tests() {
+    use super::parser::JsonParser;
+
+    let parser = JsonParser::process(
+        r##"{ "a": [1, -2, 2.5, 18446744073709551615], "b": {"c": null, "d": true}, "eA": "x\ny" }"##,
+    );
+    let root = JsonNode::new(&parser);
+    assert_eq!(
+        root.to_compact_string(),
+        serde_json::to_string(&root).unwrap()
+    );
+}
@@ -0,0 +1,221 @@
+use super::parser::ParseError;
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::rc::Rc;
+use alloc::string::String;
+use core::cell::{Cell, RefCell};
+
+fn hex4(it: &mut core::str::Chars) -> Option<u32> {
+    let mut value = 0;
+    for _ in 0..4 {
+        value = value * 16 + it.next()?.to_digit(16)?;
+    }
+    Some(value)
+}
+
+// with double quote
+pub(crate) fn write_escaped(f: &mut impl core::fmt::Write, src: &str) -> core::fmt::Result {
+    write_escaped_with(f, src, false)
+}
+
+fn write_escaped_with(
+    f: &mut impl core::fmt::Write,
+    src: &str,
+    escape_slash: bool,
+) -> core::fmt::Result {
+    f.write_char('"')?;
+    for c in src.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '/' if escape_slash => f.write_str("\\/")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            '\u{8}' => f.write_str("\\b")?,
+            '\u{c}' => f.write_str("\\f")?,
+            c if (c as u32) < 0x20 => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
+
+// json string literal with double quote. a"b => "a\"b".
+// control chars other than \b \f \n \r \t are written as \uXXXX
+pub fn escape_string(src: &str) -> String {
+    let mut dst = String::with_capacity(src.len() + 2);
+    write_escaped_with(&mut dst, src, false).unwrap();
+    dst
+}
+
+// also escapes / as \/, for embedding in html
+pub fn escape_string_slash(src: &str) -> String {
+    let mut dst = String::with_capacity(src.len() + 2);
+    write_escaped_with(&mut dst, src, true).unwrap();
+    dst
+}
+
+// \r\n and lone \r become \n. borrowed if src has no \r
+pub fn normalize_newlines(src: &str) -> Cow<'_, str> {
+    if !src.contains('\r') {
+        return Cow::Borrowed(src);
+    }
+    Cow::Owned(src.replace("\r\n", "\n").replace('\r', "\n"))
+}
+
+// content of a json string without double quote.
+// borrowed if no escape sequence. None if invalid escape.
+pub fn unescape(src: &str) -> Option<Cow<'_, str>> {
+    try_unescape(src).ok()
+}
+
+// json pointer reference token of a key as written. "a\/b" => "a~1b", "m~n" => "m~0n"
+pub fn pointer_token(key: &str) -> String {
+    let key = unescape(key).unwrap_or(Cow::Borrowed(key));
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+// unescape with ParseError::InvalidEscape at the backslash, relative to src.
+// a bad \u sequence, including a broken surrogate pair, reports its first backslash and 'u'.
+// a trailing backslash reports itself
+pub fn try_unescape(src: &str) -> Result<Cow<'_, str>, ParseError> {
+    if !src.contains('\\') {
+        return Ok(Cow::Borrowed(src));
+    }
+
+    let mut dst = String::with_capacity(src.len());
+    let mut it = src.chars();
+    while let Some(c) = it.next() {
+        if c != '\\' {
+            dst.push(c);
+            continue;
+        }
+        let at = src.len() - it.as_str().len() - 1;
+        let escape = it.next();
+        let invalid = || ParseError::InvalidEscape(at, escape.unwrap_or('\\'));
+        match escape.ok_or_else(invalid)? {
+            '"' => dst.push('"'),
+            '\\' => dst.push('\\'),
+            '/' => dst.push('/'),
+            'b' => dst.push('\u{8}'),
+            'f' => dst.push('\u{c}'),
+            'n' => dst.push('\n'),
+            'r' => dst.push('\r'),
+            't' => dst.push('\t'),
+            'u' => {
+                let high = hex4(&mut it).ok_or_else(invalid)?;
+                let code = if (0xD800..0xDC00).contains(&high) {
+                    // surrogate pair
+                    if it.next() != Some('\\') || it.next() != Some('u') {
+                        return Err(invalid());
+                    }
+                    let low = hex4(&mut it).ok_or_else(invalid)?;
+                    if !(0xDC00..0xE000).contains(&low) {
+                        return Err(invalid());
+                    }
+                    0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+                } else {
+                    high
+                };
+                dst.push(core::char::from_u32(code).ok_or_else(invalid)?);
+            }
+            _ => return Err(invalid()),
+        }
+    }
+    Ok(Cow::Owned(dst))
+}
+
+#[test]
+fn escape_string_tests() {
+    assert_eq!(r##""a\"b""##, escape_string("a\"b"));
+    assert_eq!(r##""a\nb""##, escape_string("a\nb"));
+    assert_eq!(r##""\u0001\\""##, escape_string("\u{1}\\"));
+    assert_eq!(r##""</a>""##, escape_string("</a>"));
+    assert_eq!(r##""<\/a>""##, escape_string_slash("</a>"));
+    let escaped = escape_string_slash("a\"b\n\u{1}/");
+    assert_eq!(
+        "a\"b\n\u{1}/",
+        unescape(&escaped[1..escaped.len() - 1]).unwrap()
+    );
+}
+
+#[test]
+fn normalize_newlines_tests() {
+    assert!(matches!(normalize_newlines("a\nb"), Cow::Borrowed("a\nb")));
+    assert_eq!("a\nb\nc\n\nd", normalize_newlines("a\r\nb\rc\n\r\nd"));
+}
+
+#[test]
+fn unescape_tests() {
+    assert_eq!(Some(Cow::Borrowed("abc")), unescape("abc"));
+    assert_eq!("a\"b", unescape(r##"a\"b"##).unwrap());
+    assert_eq!("\\/\n\t", unescape(r##"\\\/\n\t"##).unwrap());
+    assert_eq!("\u{e9}", unescape(r##"\u00e9"##).unwrap());
+    assert_eq!("\u{1F600}", unescape(r##"\ud83d\ude00"##).unwrap());
+    assert_eq!(None, unescape(r##"\x"##));
+    assert_eq!(None, unescape(r##"\u12"##));
+}
+
+#[test]
+fn try_unescape_tests() {
+    assert_eq!("a\n", try_unescape(r##"a\n"##).unwrap());
+    assert!(matches!(
+        try_unescape(r##"\x"##),
+        Err(ParseError::InvalidEscape(0, 'x'))
+    ));
+    assert!(matches!(
+        try_unescape(r##"a\n\u12"##),
+        Err(ParseError::InvalidEscape(3, 'u'))
+    ));
+    // lone high surrogate
+    assert!(matches!(
+        try_unescape(r##"a\ud83d!"##),
+        Err(ParseError::InvalidEscape(1, 'u'))
+    ));
+    assert!(matches!(
+        try_unescape("ab\\"),
+        Err(ParseError::InvalidEscape(2, '\\'))
+    ));
+}
+
+// opt-in memo of unescaped strings, keyed by token index.
+// use one cache per parser. see JsonNode::get_str_cached
+#[derive(Default)]
+pub struct UnescapeCache {
+    strings: RefCell<BTreeMap<usize, Rc<str>>>,
+    hits: Cell<usize>,
+}
+
+impl UnescapeCache {
+    pub fn new() -> UnescapeCache {
+        UnescapeCache::default()
+    }
+
+    pub(crate) fn get_or_insert(
+        &self,
+        index: usize,
+        f: impl FnOnce() -> Option<Rc<str>>,
+    ) -> Option<Rc<str>> {
+        if let Some(s) = self.strings.borrow().get(&index) {
+            self.hits.set(self.hits.get() + 1);
+            return Some(s.clone());
+        }
+        let s = f()?;
+        self.strings.borrow_mut().insert(index, s.clone());
+        Some(s)
+    }
+
+    // reads served from the cache
+    pub fn hits(&self) -> usize {
+        self.hits.get()
+    }
+
+    pub fn len(&self) -> usize {
+        self.strings.borrow().len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.strings.borrow().is_empty()
+    }
+}
@@ -0,0 +1,123 @@
+use super::node::{json_eq, JsonNode};
+use super::parser::JsonParser;
+use super::string::{pointer_token, unescape};
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::String;
+
+// panics unless a and b are json_eq. the message has the pointer of the first difference
+// and both documents in compact form
+#[track_caller]
+pub fn assert_json_eq(a: &str, b: &str) {
+    let (left, right) = (parse(a), parse(b));
+    let (left, right) = (JsonNode::new(&left), JsonNode::new(&right));
+    if !json_eq(&left, &right) {
+        let pointer = first_difference(&left, &right).unwrap_or_default();
+        panic!(
+            "json differs at \"{}\"\n  left: {}\n right: {}",
+            pointer,
+            left.to_compact_string(),
+            right.to_compact_string()
+        );
+    }
+}
+
+#[track_caller]
+fn parse(src: &str) -> JsonParser<'_> {
+    match JsonParser::try_process(src) {
+        Ok(parser) => parser,
+        Err(e) => panic!("invalid json: {}\n{}", e, e.render(src)),
+    }
+}
+
+// json pointer of the first value that is not json_eq. None if equal
+pub fn first_difference(a: &JsonNode, b: &JsonNode) -> Option<String> {
+    if json_eq(a, b) {
+        return None;
+    }
+    if a.array_len().is_some() && b.array_len().is_some() {
+        for (i, (x, y)) in a.array_iter().zip(b.array_iter()).enumerate() {
+            if let Some(pointer) = first_difference(&x, &y) {
+                return Some(format!("/{}{}", i, pointer));
+            }
+        }
+        // one is a prefix of the other
+        let i = a.array_len().min(b.array_len()).unwrap_or(0);
+        return Some(format!("/{}", i));
+    }
+    // members by unescaped key, as json_eq compares them
+    if let (Some(x_members), Some(y_members)) = (a.to_btreemap(), b.to_btreemap()) {
+        // document order of a, then the members only in b
+        for (k, _) in a.object_iter().chain(b.object_iter()) {
+            let name = unescape(k).unwrap_or(Cow::Borrowed(k));
+            match (x_members.get(name.as_ref()), y_members.get(name.as_ref())) {
+                (Some(x), Some(y)) => {
+                    if let Some(pointer) = first_difference(x, y) {
+                        return Some(format!("/{}{}", pointer_token(k), pointer));
+                    }
+                }
+                _ => return Some(format!("/{}", pointer_token(k))),
+            }
+        }
+    }
+    Some(String::new())
+}
+
+#[test]
+fn assert_json_eq_tests() {
+    assert_json_eq(
+        r##"{"a": [1, 2.0], "b": "x"}"##,
+        r##"{"b": "\u0078", "a": [1.0, 2]}"##,
+    );
+
+    let failed = std::panic::catch_unwind(|| {
+        assert_json_eq(
+            r##"{"a": [1, 2], "b": "x"}"##,
+            r##"{"a": [1, 3], "b": "x"}"##,
+        )
+    });
+    let message = failed.unwrap_err();
+    assert_eq!(
+        "json differs at \"/a/1\"\n  left: {\"a\":[1,2],\"b\":\"x\"}\n right: {\"a\":[1,3],\"b\":\"x\"}",
+        message.downcast_ref::<String>().unwrap()
+    );
+}
+
+#[test]
+fn first_difference_tests() {
+    let diff = |a: &str, b: &str| {
+        let (a, b) = (JsonParser::process(a), JsonParser::process(b));
+        first_difference(&JsonNode::new(&a), &JsonNode::new(&b))
+    };
+    assert_eq!(None, diff("[1, {}]", "[1.0, {}]"));
+    assert_eq!(Some(String::from("/2")), diff("[1, 2]", "[1, 2, 3]"));
+    assert_eq!(
+        Some(String::from("/b")),
+        diff(r##"{"a": 1}"##, r##"{"a": 1, "b": 2}"##)
+    );
+    assert_eq!(
+        Some(String::from("/a")),
+        diff(r##"{"a": 1}"##, r##"{"a": [1]}"##)
+    );
+    assert_eq!(Some(String::new()), diff("1", "true"));
+
+    // keys are escaped as pointer tokens
+    assert_eq!(
+        Some(String::from("/a~1b/0")),
+        diff(r##"{"a/b": [1]}"##, r##"{"a/b": [2]}"##)
+    );
+    assert_eq!(
+        Some(String::from("/m~0n")),
+        diff(r##"{"m~n": 1}"##, r##"{}"##)
+    );
+    assert_eq!(
+        Some(String::from("/c~1d")),
+        diff(r##"{}"##, r##"{"c\/d": 1}"##)
+    );
+    // the same key written two ways
+    assert_eq!(None, diff(r##"{"c/d": 1}"##, r##"{"c\/d": 1}"##));
+    assert_eq!(
+        Some(String::from("/c~1d")),
+        diff(r##"{"c/d": 1}"##, r##"{"c\/d": 2}"##)
+    );
+}
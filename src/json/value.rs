@@ -0,0 +1,355 @@
+use super::node::JsonNode;
+use super::parser::{JsonValue, Number};
+use super::string::{unescape, write_escaped};
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+// json value that does not borrow the source
+#[derive(Debug, Clone, PartialEq)]
+pub enum OwnedValue {
+    Null,
+    Bool(bool),
+    Number(Number),
+    String(String),
+    Array(Vec<OwnedValue>),
+    Object(Vec<(String, OwnedValue)>), // keep member order
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SetError {
+    // the value before the segment is neither object, array nor null. path up to the segment
+    NotContainer(String),
+    // the segment is not a number but the value is an array
+    NotIndex(String),
+}
+
+impl fmt::Display for SetError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SetError::NotContainer(path) => write!(f, "{} is not in an object or array", path),
+            SetError::NotIndex(path) => write!(f, "{} is not an array index", path),
+        }
+    }
+}
+
+// compact json
+impl fmt::Display for OwnedValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OwnedValue::Null => write!(f, "null"),
+            OwnedValue::Bool(value) => write!(f, "{}", value),
+            OwnedValue::Number(value) => write!(f, "{}", value),
+            OwnedValue::String(value) => write_escaped(f, value),
+            OwnedValue::Array(values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write!(f, "{}", value)?;
+                }
+                write!(f, "]")
+            }
+            OwnedValue::Object(members) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in members.iter().enumerate() {
+                    if i > 0 {
+                        write!(f, ",")?;
+                    }
+                    write_escaped(f, key)?;
+                    write!(f, ":{}", value)?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+impl OwnedValue {
+    pub fn from_node(node: &JsonNode) -> OwnedValue {
+        match node.value() {
+            JsonValue::Null() => OwnedValue::Null,
+            JsonValue::True() => OwnedValue::Bool(true),
+            JsonValue::False() => OwnedValue::Bool(false),
+            JsonValue::Number(..) => node
+                .as_number()
+                .map_or(OwnedValue::Null, OwnedValue::Number),
+            JsonValue::String(_) => node
+                .get_str()
+                .map_or(OwnedValue::Null, |s| OwnedValue::String(s.into_owned())),
+            JsonValue::ArrayOpen(_) => OwnedValue::Array(
+                node.array_iter()
+                    .map(|v| OwnedValue::from_node(&v))
+                    .collect(),
+            ),
+            JsonValue::ObjectOpen(_) => OwnedValue::Object(
+                node.object_iter()
+                    .map(|(k, v)| {
+                        let key = unescape(k).map_or_else(|| String::from(k), |k| k.into_owned());
+                        (key, OwnedValue::from_node(&v))
+                    })
+                    .collect(),
+            ),
+        }
+    }
+
+    // objects are merged member by member, anything else is replaced by other.
+    // null in other does not delete
+    pub fn merge(&mut self, other: OwnedValue) {
+        match (self, other) {
+            (OwnedValue::Object(members), OwnedValue::Object(others)) => {
+                for (key, value) in others {
+                    match members.iter_mut().find(|(k, _)| *k == key) {
+                        Some((_, member)) => member.merge(value),
+                        None => members.push((key, value)),
+                    }
+                }
+            }
+            (this, other) => *this = other,
+        }
+    }
+
+    // dotted path as JsonNode::flatten. "" is the value itself.
+    // null and missing values become an array for a numeric segment, otherwise an object.
+    // arrays are extended with nulls. on error, containers created so far are kept
+    pub fn set_path(&mut self, path: &str, value: OwnedValue) -> Result<(), SetError> {
+        if path.is_empty() {
+            *self = value;
+            return Ok(());
+        }
+        let segments: Vec<&str> = path.split('.').collect();
+        self.set_segments(&segments, 0, value)
+    }
+
+    fn set_segments(
+        &mut self,
+        segments: &[&str],
+        depth: usize,
+        value: OwnedValue,
+    ) -> Result<(), SetError> {
+        let segment = segments[depth];
+        let at = || segments[..=depth].join(".");
+        if *self == OwnedValue::Null {
+            *self = if segment.parse::<usize>().is_ok() {
+                OwnedValue::Array(Vec::new())
+            } else {
+                OwnedValue::Object(Vec::new())
+            };
+        }
+        let child = match self {
+            OwnedValue::Object(members) => match members.iter().position(|(k, _)| k == segment) {
+                Some(i) => &mut members[i].1,
+                None => {
+                    members.push((String::from(segment), OwnedValue::Null));
+                    &mut members.last_mut().unwrap().1
+                }
+            },
+            OwnedValue::Array(items) => {
+                let i = segment
+                    .parse::<usize>()
+                    .map_err(|_| SetError::NotIndex(at()))?;
+                if items.len() <= i {
+                    items.resize(i + 1, OwnedValue::Null);
+                }
+                &mut items[i]
+            }
+            _ => return Err(SetError::NotContainer(at())),
+        };
+        if depth + 1 < segments.len() {
+            child.set_segments(segments, depth + 1, value)
+        } else {
+            *child = value;
+            Ok(())
+        }
+    }
+
+    // renames the first member named old in place. false unless self is an object with old.
+    // a different member already named new is removed, so the renamed value overwrites it
+    pub fn rename_key(&mut self, old: &str, new: &str) -> bool {
+        let members = match self {
+            OwnedValue::Object(members) => members,
+            _ => return false,
+        };
+        let i = match members.iter().position(|(k, _)| k == old) {
+            Some(i) => i,
+            None => return false,
+        };
+        members[i].0 = String::from(new);
+        let mut j = 0;
+        members.retain(|(k, _)| {
+            let keep = j == i || k != new;
+            j += 1;
+            keep
+        });
+        true
+    }
+
+    pub fn get(&self, key: &str) -> Option<&OwnedValue> {
+        match self {
+            OwnedValue::Object(members) => members.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+}
+
+#[test]
+fn from_node_tests() {
+    use super::parser::JsonParser;
+
+    let parser = JsonParser::process(r##"{"a": [1, 2.5, "x\ny"], "b": {"c": null, "d": true}}"##);
+    let value = OwnedValue::from_node(&JsonNode::new(&parser));
+    assert_eq!(
+        OwnedValue::Object(vec![
+            (
+                "a".to_string(),
+                OwnedValue::Array(vec![
+                    OwnedValue::Number(Number::Int(1)),
+                    OwnedValue::Number(Number::Float(2.5)),
+                    OwnedValue::String("x\ny".to_string()),
+                ])
+            ),
+            (
+                "b".to_string(),
+                OwnedValue::Object(vec![
+                    ("c".to_string(), OwnedValue::Null),
+                    ("d".to_string(), OwnedValue::Bool(true)),
+                ])
+            ),
+        ]),
+        value
+    );
+    assert_eq!(Some(&OwnedValue::Null), value.get("b").unwrap().get("c"));
+}
+
+#[test]
+fn merge_tests() {
+    use super::parser::JsonParser;
+    let parse = |src: &str| OwnedValue::from_node(&JsonNode::new(&JsonParser::process(src)));
+
+    let mut base = parse(r##"{"asset": {"version": "2.0", "generator": "a"}, "scene": 0}"##);
+    base.merge(parse(
+        r##"{"asset": {"generator": "b", "copyright": "c"}, "nodes": []}"##,
+    ));
+    assert_eq!(
+        parse(
+            r##"{"asset": {"version": "2.0", "generator": "b", "copyright": "c"}, "scene": 0, "nodes": []}"##
+        ),
+        base
+    );
+
+    let mut base = parse(r##"{"scale": 1, "keep": null}"##);
+    base.merge(parse(r##"{"scale": [1, 2, 3], "keep": null}"##));
+    assert_eq!(parse(r##"{"scale": [1, 2, 3], "keep": null}"##), base);
+
+    let mut base = parse("[1, 2]");
+    base.merge(parse("[3]"));
+    assert_eq!(parse("[3]"), base);
+}
+
+#[test]
+fn set_path_tests() {
+    use super::parser::JsonParser;
+    let parse = |src: &str| OwnedValue::from_node(&JsonNode::new(&JsonParser::process(src)));
+
+    let mut value = parse("{}");
+    value.set_path("a.b.0", parse("1")).unwrap();
+    assert_eq!(parse(r##"{"a": {"b": [1]}}"##), value);
+    value.set_path("a.b.2", parse("true")).unwrap();
+    assert_eq!(parse(r##"{"a": {"b": [1, null, true]}}"##), value);
+
+    // overwrite a leaf
+    let mut value = parse(r##"{"a": {"b": "x", "c": 1}}"##);
+    value.set_path("a.b", parse("[2]")).unwrap();
+    assert_eq!(parse(r##"{"a": {"b": [2], "c": 1}}"##), value);
+    value.set_path("", parse("null")).unwrap();
+    assert_eq!(OwnedValue::Null, value);
+
+    let mut value = parse(r##"{"a": 1, "b": []}"##);
+    assert_eq!(
+        Err(SetError::NotContainer("a.x".to_string())),
+        value.set_path("a.x", parse("2"))
+    );
+    assert_eq!(
+        Err(SetError::NotIndex("b.x".to_string())),
+        value.set_path("b.x", parse("2"))
+    );
+}
+
+#[test]
+fn rename_key_tests() {
+    use super::parser::JsonParser;
+    let parse = |src: &str| OwnedValue::from_node(&JsonNode::new(&JsonParser::process(src)));
+
+    let mut value = parse(r##"{"a": 1, "KHR_old": {"x": 2}, "c": 3}"##);
+    assert!(value.rename_key("KHR_old", "KHR_new"));
+    assert_eq!(parse(r##"{"a": 1, "KHR_new": {"x": 2}, "c": 3}"##), value);
+
+    assert!(!value.rename_key("none", "b"));
+    assert_eq!(parse(r##"{"a": 1, "KHR_new": {"x": 2}, "c": 3}"##), value);
+    assert!(!parse("[1]").rename_key("a", "b"));
+
+    // overwrite
+    assert!(value.rename_key("c", "a"));
+    assert_eq!(parse(r##"{"KHR_new": {"x": 2}, "a": 3}"##), value);
+    assert!(value.rename_key("a", "a"));
+    assert_eq!(parse(r##"{"KHR_new": {"x": 2}, "a": 3}"##), value);
+}
+
+#[cfg(test)]
+fn arb_value() -> impl proptest::strategy::Strategy<Value = OwnedValue> {
+    use proptest::prelude::*;
+
+    let leaf = prop_oneof![
+        Just(OwnedValue::Null),
+        any::<bool>().prop_map(OwnedValue::Bool),
+        any::<i64>().prop_map(|n| OwnedValue::Number(Number::Int(n))),
+        (i64::MAX as u64 + 1..=u64::MAX).prop_map(|n| OwnedValue::Number(Number::UInt(n))),
+        any::<f64>()
+            .prop_filter("json has no nan or inf", |n| n.is_finite())
+            .prop_map(|n| OwnedValue::Number(Number::Float(n))),
+        any::<String>().prop_map(OwnedValue::String),
+    ];
+    leaf.prop_recursive(4, 64, 8, |inner| {
+        prop_oneof![
+            proptest::collection::vec(inner.clone(), 0..8).prop_map(OwnedValue::Array),
+            proptest::collection::vec((any::<String>(), inner), 0..8).prop_map(OwnedValue::Object),
+        ]
+    })
+}
+
+#[cfg(test)]
+fn round_trip(value: &OwnedValue) -> OwnedValue {
+    use super::parser::JsonParser;
+
+    let src = value.to_string();
+    let parser = JsonParser::try_process(&src).unwrap();
+    OwnedValue::from_node(&JsonNode::new(&parser))
+}
+
+#[cfg(test)]
+proptest::proptest! {
+    #[test]
+    fn round_trip_tests(value in arb_value()) {
+        proptest::prop_assert_eq!(&value, &round_trip(&value));
+    }
+}
+
+#[test]
+fn round_trip_regression_tests() {
+    // escaped quote and control char in a key, exponent without sign
+    let value = OwnedValue::Object(vec![(
+        "k\"\\\u{1}".to_string(),
+        OwnedValue::Array(vec![
+            OwnedValue::Number(Number::Float(1e20)),
+            OwnedValue::Number(Number::Float(-0.5e-7)),
+            OwnedValue::Number(Number::UInt(u64::MAX)),
+            OwnedValue::String("\u{1F600}\n".to_string()),
+        ]),
+    )]);
+    assert_eq!(
+        r##"{"k\"\\\u0001":[1e20,-5e-8,18446744073709551615,"😀\n"]}"##,
+        value.to_string()
+    );
+    assert_eq!(value, round_trip(&value));
+}
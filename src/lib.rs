@@ -0,0 +1,7 @@
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
+extern crate alloc;
+
+pub mod json;
+#[cfg(feature = "wasm")]
+pub mod wasm;
@@ -1,17 +1,48 @@
-use json::schema::parser::JsonSchemaParser;
+use jsonprops::json::schema::parser::{JsonSchema, JsonSchemaParser};
 
-mod json;
+// keywords the schema parser does not read, nested schemas included
+fn print_unknown_keywords(schema: &JsonSchema) {
+    for (keyword, value) in &schema.unknown_keywords {
+        println!("{} => {}", keyword, value);
+    }
+    // local references are copies of definitions, which are listed anyway
+    let file_ref = match &schema.reference {
+        Some(reference) if !reference.starts_with('#') => schema.ref_schema.as_deref(),
+        _ => None,
+    };
+    let children = file_ref
+        .into_iter()
+        .chain(&schema.all_of)
+        .chain(schema.properties.iter().map(|(_, s)| s))
+        .chain(schema.definitions.iter().map(|(_, s)| s))
+        .chain(schema.pattern_properties.iter().map(|(_, s)| s))
+        .chain(schema.additional_properties.as_deref())
+        .chain(schema.items.as_deref())
+        .chain(&schema.prefix_items)
+        .chain(schema.contains.as_deref())
+        .chain(schema.property_names.as_deref());
+    for child in children {
+        print_unknown_keywords(child);
+    }
+}
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
     if args.len() < 2 {
-        println!("usage: jsonprop.exe {{input.json}}");
+        println!("usage: jsonprop.exe {{input.json}} [--examples]");
         return;
     }
 
-    let src = std::fs::read_to_string(&args[1]).unwrap();
+    let parser = JsonSchemaParser::from_path(std::path::Path::new(&args[1])).unwrap();
 
-    let parser = JsonSchemaParser::parse(&src);
+    // one example of the root schema per line
+    if args.iter().skip(2).any(|arg| arg == "--examples") {
+        for example in &parser.root.examples {
+            println!("{}", example);
+        }
+        return;
+    }
 
+    print_unknown_keywords(&parser.root);
     print!("done");
 }
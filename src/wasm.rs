@@ -0,0 +1,67 @@
+use crate::json::node::JsonNode;
+use crate::json::parser::{JsonParser, ParseError};
+use crate::json::schema::parser::JsonSchema;
+use crate::json::schema::validate::ValidationError;
+use js_sys::Array;
+use wasm_bindgen::prelude::*;
+
+fn parse(src: &str) -> Result<JsonParser<'_>, JsValue> {
+    JsonParser::try_process(src).map_err(|error| JsValue::from_str(&error.to_string()))
+}
+
+// [[path, value], ...]
+#[wasm_bindgen]
+pub fn parse_to_flat(src: &str) -> Result<JsValue, JsValue> {
+    let parser = parse(src)?;
+    let props = Array::new();
+    for (path, value) in JsonNode::new(&parser).flatten() {
+        props.push(&Array::of2(
+            &JsValue::from_str(&path),
+            &JsValue::from_str(value),
+        ));
+    }
+    Ok(props.into())
+}
+
+fn validation_errors(schema: &str, instance: &str) -> Result<Vec<ValidationError>, ParseError> {
+    let schema_parser = JsonParser::try_process(schema)?;
+    let schema = JsonSchema::parse(JsonNode::new(&schema_parser));
+    let instance_parser = JsonParser::try_process(instance)?;
+    Ok(schema.validate(&JsonNode::new(&instance_parser)))
+}
+
+// [[pointer, message], ...]. empty if valid
+#[wasm_bindgen]
+pub fn validate(schema: &str, instance: &str) -> Result<JsValue, JsValue> {
+    let errors = Array::new();
+    for error in validation_errors(schema, instance)
+        .map_err(|error| JsValue::from_str(&error.to_string()))?
+    {
+        errors.push(&Array::of2(
+            &JsValue::from_str(&error.pointer),
+            &JsValue::from_str(&error.message),
+        ));
+    }
+    Ok(errors.into())
+}
+
+#[test]
+fn validation_errors_tests() {
+    // local $ref
+    let schema = r##"{
+    "properties": { "count": { "$ref": "#/definitions/count" }, "name": { "$ref": "#/$defs/name" } },
+    "definitions": { "count": { "type": "integer" } },
+    "$defs": { "name": { "type": "string" } }
+}"##;
+    assert!(validation_errors(schema, r##"{"count": 1, "name": "a"}"##)
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        2,
+        validation_errors(schema, r##"{"count": "x", "name": 1}"##)
+            .unwrap()
+            .len()
+    );
+    assert!(validation_errors("{", "1").is_err());
+    assert!(validation_errors("{}", "[").is_err());
+}